@@ -1,477 +1,1567 @@
+#[cfg(feature = "serde")]
+extern crate serde;
+
 /// A module to create SQL-based queries programmatically.
 pub mod query_builder {
-    use std::collections::HashMap;
+    use std::borrow::Cow;
+    use std::error::Error;
     use std::fmt;
+    use std::hash::{Hash, Hasher};
+    use std::marker::PhantomData;
+
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    /// `ALTER TABLE`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct AlterTable<'a> {
+        table: Ident<'a>,
+        alterations: Vec<Alteration<'a>>,
+    }
+
+    /// `SELECT` queries combined with `UNION`, `INTERSECT` or `EXCEPT`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Compound<'a> {
+        first: Select<'a>,
+        rest: Vec<(SetOperation, Select<'a>)>,
+        order: Option<Vec<OrderBy<'a>>>,
+        limit: usize,
+        offset: usize,
+        ctes: Option<Vec<Cte<'a>>>,
+    }
+
+    /// `CREATE INDEX`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct CreateIndex<'a> {
+        name: Ident<'a>,
+        table: Ident<'a>,
+        unique: bool,
+        concurrently: bool,
+        if_not_exists: bool,
+        method: Option<IndexMethod>,
+        keys: Vec<OrderBy<'a>>,
+        conditions: Option<Vec<Expr<'a>>>,
+    }
+
+    /// `CREATE TABLE`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct CreateTable<'a> {
+        table: Ident<'a>,
+        if_not_exists: bool,
+        columns: Vec<Column<'a>>,
+        constraints: Option<Vec<TableConstraint<'a>>>,
+    }
 
     /// `DELETE`
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Delete<'a> {
-        table: &'a str,
-        conditions: Option<Vec<&'a str>>,
+        table: Ident<'a>,
+        joins: Option<Vec<JoinClause<'a>>>,
+        conditions: Option<Vec<Expr<'a>>>,
+        returns: Option<Vec<Expr<'a>>>,
+        ctes: Option<Vec<Cte<'a>>>,
+    }
+
+    /// `DROP TABLE`, `DROP INDEX` or `DROP VIEW`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct DropObject<'a> {
+        kind: ObjectKind,
+        name: Ident<'a>,
+        table: Option<Ident<'a>>,
+        if_exists: bool,
+        cascade: bool,
     }
 
     /// `INSERT`
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Insert<'a> {
-        table: &'a str,
-        values: HashMap<&'a str, &'a str>,
-        returns: Option<Vec<&'a str>>,
+        table: Ident<'a>,
+        values: Assignments<'a>,
+        columns: Option<Vec<Ident<'a>>>,
+        rows: Option<Vec<InsertRow<'a>>>,
+        source: Option<Select<'a>>,
+        conflict: Option<OnConflict<'a>>,
+        returns: Option<Vec<Expr<'a>>>,
+        ctes: Option<Vec<Cte<'a>>>,
     }
 
     /// `SELECT`
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Select<'a> {
-        table: &'a str,
-        aliases: Option<HashMap<&'a str, &'a str>>,
-        fields: Option<Vec<&'a str>>,
-        order: Option<Vec<(&'a str, Order)>>,
+        table: Table<'a>,
+        aliases: Option<Vec<(Ident<'a>, Ident<'a>)>>,
+        fields: Option<Vec<(Expr<'a>, Option<Ident<'a>>)>>,
+        distinct: Option<Distinct<'a>>,
+        order: Option<Vec<OrderBy<'a>>>,
         joins: Option<Vec<JoinClause<'a>>>,
-        groupings: Option<Vec<&'a str>>,
-        havings: Option<Vec<&'a str>>,
-        conditions: Option<Vec<&'a str>>,
+        groupings: Option<Vec<Expr<'a>>>,
+        havings: Option<Vec<Expr<'a>>>,
+        windows: Option<Vec<(Ident<'a>, Window<'a>)>>,
+        conditions: Option<Vec<Expr<'a>>>,
         limit: usize,
         offset: usize,
+        locks: Option<Vec<Lock<'a>>>,
+        ctes: Option<Vec<Cte<'a>>>,
     }
 
     /// `UPDATE`
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct Update<'a> {
-        table: &'a str,
-        values: HashMap<&'a str, &'a str>,
-        conditions: Option<Vec<&'a str>>,
-        returns: Option<Vec<&'a str>>,
+        table: Ident<'a>,
+        values: Assignments<'a>,
+        joins: Option<Vec<JoinClause<'a>>>,
+        conditions: Option<Vec<Expr<'a>>>,
+        returns: Option<Vec<Expr<'a>>>,
+        ctes: Option<Vec<Cte<'a>>>,
     }
 
     /// A helper struct for `JOIN` clause
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     struct JoinClause<'a> {
-        table: &'a str,
-        on_left: &'a str,
-        on_right: &'a str,
+        table: Table<'a>,
+        constraint: JoinConstraint<'a>,
         kind: Join,
     }
 
+    /// How the rows of a `JOIN` clause are matched
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum JoinConstraint<'a> {
+        /// Every pair of rows (e.g., `CROSS JOIN`)
+        None,
+        /// Pairs of rows that meet a condition (`ON`)
+        On(Expr<'a>),
+        /// Pairs of rows with equal values in the named columns (`USING`)
+        Using(Vec<Ident<'a>>),
+        /// Pairs of rows with equal values in all columns with the same name (`NATURAL`)
+        Natural,
+    }
+
+    /// A lock on the rows of a `SELECT`'s result set (`FOR UPDATE` or `FOR SHARE` clause)
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Lock<'a> {
+        strength: LockStrength,
+        tables: Option<Vec<Ident<'a>>>,
+        wait: LockWait,
+    }
+
+    /// The kind of lock taken on rows
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum LockStrength {
+        /// `FOR UPDATE`
+        Update,
+        /// `FOR NO KEY UPDATE` (PostgreSQL only)
+        NoKeyUpdate,
+        /// `FOR SHARE`
+        Share,
+        /// `FOR KEY SHARE` (PostgreSQL only)
+        KeyShare,
+    }
+
+    /// What happens when rows are already locked by another transaction
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum LockWait {
+        /// Wait for the other transaction
+        Wait,
+        /// Fail immediately (`NOWAIT`)
+        NoWait,
+        /// Leave the locked rows out of the result set (`SKIP LOCKED`)
+        SkipLocked,
+    }
+
+    /// How duplicate rows are removed from a `SELECT`'s result set
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum Distinct<'a> {
+        /// Rows whose fields are all equal (`DISTINCT`)
+        Rows,
+        /// Rows whose values of the expressions are equal, keeping the first (`DISTINCT ON`)
+        On(Vec<Expr<'a>>),
+    }
+
+    /// A source of rows in a `FROM` or `JOIN` clause
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Table<'a> {
+        /// A table (or view) by name
+        Named(Ident<'a>),
+        /// A subquery, which must be given an alias (`(SELECT ...) AS alias`)
+        Derived(Box<Select<'a>>, Ident<'a>),
+        /// A subquery that can refer to the tables before it, which must be given an alias
+        /// (`LATERAL (SELECT ...) AS alias`)
+        Lateral(Box<Select<'a>>, Ident<'a>),
+    }
+
+    /// Any query that can be generated on its own, e.g., as a named subquery of a `WITH` clause
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Statement<'a> {
+        Select(Box<Select<'a>>),
+        Compound(Box<Compound<'a>>),
+        Insert(Box<Insert<'a>>),
+        Update(Box<Update<'a>>),
+        Delete(Box<Delete<'a>>),
+        CreateTable(Box<CreateTable<'a>>),
+        CreateIndex(Box<CreateIndex<'a>>),
+        AlterTable(Box<AlterTable<'a>>),
+        Drop(Box<DropObject<'a>>),
+        /// SQL written verbatim
+        Raw(Cow<'a, str>),
+    }
+
+    /// A named subquery of a `WITH` clause (common table expression)
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct Cte<'a> {
+        name: Ident<'a>,
+        columns: Option<Vec<Ident<'a>>>,
+        recursive: bool,
+        query: Statement<'a>,
+    }
+
+    /// An identifier (e.g., a table or column name), optionally qualified by the names it
+    /// belongs to (e.g., `schema.table` or `table.column`)
+    ///
+    /// Each part of the identifier is quoted if it is a keyword or is not a plain name.
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Ident<'a> {
+        parts: Vec<Cow<'a, str>>,
+    }
+
+    /// Field values of an `INSERT` or `UPDATE`, in the order they were first set
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct Assignments<'a>(Vec<(Ident<'a>, Expr<'a>)>);
+
+    /// A row of a multi-row `INSERT`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum InsertRow<'a> {
+        /// Values in the order of the `INSERT`'s columns
+        Positional(Vec<Expr<'a>>),
+        /// Values paired with their field names
+        Named(Assignments<'a>),
+    }
+
+    /// A helper struct for an `INSERT`'s conflict handling (`ON CONFLICT` clause)
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct OnConflict<'a> {
+        target: ConflictTarget<'a>,
+        values: Assignments<'a>,
+        conditions: Option<Vec<Expr<'a>>>,
+    }
+
+    /// The unique index or constraint whose violation is handled by `ON CONFLICT`
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum ConflictTarget<'a> {
+        Any,
+        Columns(Vec<Ident<'a>>),
+        Constraint(Ident<'a>),
+    }
+
+    /// A type that can be inserted as a row (e.g., a struct whose fields map to columns)
+    pub trait Row<'a> {
+        /// The row's field names and values
+        fn into_fields(self) -> Vec<(&'a str, Expr<'a>)>;
+    }
+
+    /// A reusable bundle of conditions, joins and ordering, which can be applied to `SELECT`,
+    /// `UPDATE` and `DELETE` query builders (e.g., a tenant filter)
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Scope<'a> {
+        conditions: Vec<Expr<'a>>,
+        joins: Vec<JoinClause<'a>>,
+        order: Vec<OrderBy<'a>>,
+    }
+
+    /// A type that can be applied to a query as a scope (e.g., a struct holding a tenant ID)
+    pub trait IntoScope<'a> {
+        /// The scope's conditions, joins and ordering
+        fn into_scope(self) -> Scope<'a>;
+    }
+
     /// The direction of an `ORDER` clause's expression
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum Order {
         Asc,
         Desc,
     }
 
+    /// Where `NULL` values are placed by an `ORDER` clause's expression
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Nulls {
+        First,
+        Last,
+    }
+
+    /// An expression (or output column position) of an `ORDER` clause, with its direction and
+    /// options
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct OrderBy<'a> {
+        key: SortKey<'a>,
+        direction: Order,
+        nulls: Option<Nulls>,
+        collation: Option<Cow<'a, str>>,
+    }
+
+    /// What an `ORDER` clause's expression sorts by
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum SortKey<'a> {
+        Expr(Expr<'a>),
+        /// A column of the result set, counting from 1
+        Position(usize),
+    }
+
     /// The type of `JOIN` to perform
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub enum Join {
         Left,
         Inner,
+        Right,
+        /// `FULL OUTER`
+        Full,
+        /// Every pair of rows, without a condition
+        Cross,
     }
 
-    /// Combine a vector of `String`s, with the `sep` `str` between each value
-    fn join(v: &[&str], sep: &str) -> String {
-        let mut s = String::new();
-        let last_i = v.len() - 1;
-        for (i, val) in v.iter().enumerate() {
-            s += val;
-            if i != last_i {
-                s += sep;
-            }
-        }
-        s
+    /// The rows a window function is evaluated over (`OVER (...)`), or a named window of a
+    /// `WINDOW` clause
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Window<'a> {
+        base: Option<Ident<'a>>,
+        partitions: Option<Vec<Expr<'a>>>,
+        order: Option<Vec<OrderBy<'a>>>,
+        frame: Option<(FrameUnits, FrameBound, FrameBound)>,
     }
 
-    impl<'a> fmt::Display for Delete<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.build())
-        }
+    /// How the frame of a window is measured
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum FrameUnits {
+        /// By number of rows (`ROWS`)
+        Rows,
+        /// By the value of the window's `ORDER BY` expression (`RANGE`)
+        Range,
     }
 
-    impl<'a> fmt::Display for Insert<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.build())
-        }
+    /// The start or end of the frame of a window, relative to the current row
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum FrameBound {
+        UnboundedPreceding,
+        Preceding(u64),
+        CurrentRow,
+        Following(u64),
+        UnboundedFollowing,
     }
 
-    impl<'a> fmt::Display for Select<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.build())
-        }
+    /// A column definition of a `CREATE TABLE` query
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Column<'a> {
+        name: Ident<'a>,
+        data_type: DataType<'a>,
+        not_null: bool,
+        default: Option<Expr<'a>>,
+        auto_increment: bool,
+        primary_key: bool,
+        unique: bool,
+        checks: Vec<Expr<'a>>,
+        references: Option<References<'a>>,
     }
 
-    impl<'a> fmt::Display for Update<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.build())
-        }
+    /// The type of a column, written as the closest equivalent for each dialect
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum DataType<'a> {
+        SmallInt,
+        Integer,
+        BigInt,
+        /// Single-precision floating-point number
+        Real,
+        /// Double-precision floating-point number
+        Double,
+        /// Exact number with a precision and scale (`NUMERIC` or `DECIMAL`)
+        Decimal(u8, u8),
+        Boolean,
+        /// Fixed-length string
+        Char(u32),
+        /// Variable-length string with a maximum length
+        VarChar(u32),
+        Text,
+        /// Binary string (`BYTEA` or `BLOB`)
+        Bytes,
+        Date,
+        Time,
+        /// Date and time, without a time zone
+        Timestamp,
+        Json,
+        Uuid,
+        /// Type written verbatim
+        Custom(Cow<'a, str>),
     }
 
-    impl<'a> Delete<'a> {
-        /// Construct a new `DELETE` query builder
-        pub fn new(table: &'a str) -> Self {
-            Delete {
-                table,
-                conditions: None,
-            }
+    /// The table and columns referred to by a foreign key (`REFERENCES`)
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct References<'a> {
+        table: Ident<'a>,
+        columns: Vec<Ident<'a>>,
+        on_delete: Option<ReferentialAction>,
+        on_update: Option<ReferentialAction>,
+    }
+
+    /// What happens to referencing rows when a referenced row is deleted or updated
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum ReferentialAction {
+        NoAction,
+        Restrict,
+        Cascade,
+        SetNull,
+        SetDefault,
+    }
+
+    /// A constraint on one or more columns of a `CREATE TABLE` query
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum TableConstraint<'a> {
+        PrimaryKey(Vec<Ident<'a>>),
+        Unique(Vec<Ident<'a>>),
+        Check(Expr<'a>),
+        ForeignKey(Vec<Ident<'a>>, References<'a>),
+    }
+
+    /// The data structure of an index (`USING`)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum IndexMethod {
+        BTree,
+        Hash,
+        /// Generalized inverted index (PostgreSQL), e.g., for arrays and `JSONB`
+        Gin,
+        /// Generalized search tree (PostgreSQL), e.g., for geometric types and ranges
+        Gist,
+        /// Block range index (PostgreSQL)
+        Brin,
+    }
+
+    /// A change to a table made by an `ALTER TABLE` query
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum Alteration<'a> {
+        AddColumn(Column<'a>),
+        DropColumn(Ident<'a>),
+        RenameColumn(Ident<'a>, Ident<'a>),
+        AlterType(Ident<'a>, DataType<'a>),
+        SetDefault(Ident<'a>, Expr<'a>),
+        DropDefault(Ident<'a>),
+        SetNotNull(Ident<'a>),
+        DropNotNull(Ident<'a>),
+        AddConstraint(Ident<'a>, TableConstraint<'a>),
+        DropConstraint(Ident<'a>),
+        RenameTo(Ident<'a>),
+    }
+
+    /// The kind of object removed by a `DROP` query
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum ObjectKind {
+        Table,
+        Index,
+        View,
+    }
+
+    /// The operation combining a `SELECT` query with the ones before it
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum SetOperation {
+        /// Rows of either query, without duplicates
+        Union,
+        /// Rows of either query, with duplicates
+        UnionAll,
+        /// Rows of both queries
+        Intersect,
+        /// Rows of the first query that are not in the second
+        Except,
+    }
+
+    /// The SQL dialect a query is generated for
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Dialect {
+        #[default]
+        Postgres,
+        MySql,
+        Sqlite,
+    }
+
+    /// An error encountered while generating a query
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum BuildError {
+        /// The dialect cannot express a clause used by the query
+        Unsupported {
+            dialect: Dialect,
+            feature: &'static str,
+        },
+        /// A row of an `INSERT` (counting from 0) does not supply the same columns as the others
+        RowMismatch { row: usize },
+        /// An `INSERT ... SELECT` does not specify the columns to insert
+        MissingColumns,
+        /// A query supplies a different number of columns than expected
+        ColumnCountMismatch { expected: usize, found: usize },
+        /// Two clauses of a query cannot be used together
+        Incompatible {
+            clause: &'static str,
+            with: &'static str,
+        },
+        /// The `DISTINCT ON` expressions of a query are not its leading `ORDER BY` expressions
+        DistinctOnOrder,
+        /// A clause was given an empty list of columns (or expressions)
+        EmptyColumns { clause: &'static str },
+        /// A query uses a clause without another clause it depends on
+        MissingClause {
+            clause: &'static str,
+            requires: &'static str,
+        },
+        /// Verbatim SQL has a different number of `?` markers than values bound to it
+        BoundValueMismatch { markers: usize, values: usize },
+        /// A window frame starts after it ends (e.g., starts at `UNBOUNDED FOLLOWING`)
+        InvalidFrame,
+    }
+
+    /// A value bound to a query parameter
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Value {
+        Null,
+        Bool(bool),
+        Int(i64),
+        Float(f64),
+        Text(String),
+        Bytes(Vec<u8>),
+    }
+
+    /// A SQL expression, used for conditions, fields, and values
+    ///
+    /// Expressions can be combined with `and`, `or`, and `not`; they are parenthesized as needed
+    /// when the query is generated.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Expr<'a> {
+        /// SQL written verbatim
+        Raw(Cow<'a, str>),
+        /// SQL written verbatim, with each `?` (outside quotes) replaced by a placeholder bound
+        /// to the corresponding value
+        Bound(Cow<'a, str>, Vec<Value>),
+        /// A column (or other identifier)
+        Column(Ident<'a>),
+        /// A value bound as a query parameter
+        Value(Value),
+        /// `a AND b AND ...`
+        And(Vec<Expr<'a>>),
+        /// `a OR b OR ...`
+        Or(Vec<Expr<'a>>),
+        /// `NOT a`
+        Not(Box<Expr<'a>>),
+        /// `a = b`, `a < b`, `a LIKE b`, etc.
+        Compare(Box<Expr<'a>>, Operator, Box<Expr<'a>>),
+        /// `a IN (b, c, ...)`
+        In(Box<Expr<'a>>, Vec<Expr<'a>>),
+        /// `a IN (SELECT ...)`
+        InSubquery(Box<Expr<'a>>, Box<Select<'a>>),
+        /// `a BETWEEN b AND c`
+        Between(Box<Expr<'a>>, Box<Expr<'a>>, Box<Expr<'a>>),
+        /// `a IS NULL`
+        IsNull(Box<Expr<'a>>),
+        /// `a IS NOT NULL`
+        IsNotNull(Box<Expr<'a>>),
+        /// `EXISTS (SELECT ...)`
+        Exists(Box<Select<'a>>),
+        /// A subquery that returns a single value, `(SELECT ...)`
+        Subquery(Box<Select<'a>>),
+        /// The value a conflicting `INSERT` proposed for a column (`EXCLUDED.a` or `VALUES(a)`)
+        Excluded(Ident<'a>),
+        /// A window function evaluated over a window of rows (`a OVER (...)`)
+        Over(Box<Expr<'a>>, Box<Window<'a>>),
+    }
+
+    /// A binary comparison operator
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum Operator {
+        Eq,
+        Ne,
+        Lt,
+        Le,
+        Gt,
+        Ge,
+        Like,
+        NotLike,
+    }
+
+    /// Conversion into an operand of an expression
+    ///
+    /// Expressions are used as-is; anything convertible to a `Value` (including strings) is bound
+    /// as a query parameter.
+    pub trait Operand<'a> {
+        fn into_expr(self) -> Expr<'a>;
+    }
+
+    /// A field that can be set to a `V`: a name, which can be set to any operand, or a column
+    /// declared with `table!`, which can only be set to values of its type
+    pub trait Assign<'a, V> {
+        /// The field's name and its new value
+        fn assign(self, value: V) -> (Ident<'a>, Expr<'a>);
+    }
+
+    /// A column declared with `table!`, qualified by an alias of its table
+    #[derive(Debug, Clone, Copy)]
+    pub struct Aliased<C> {
+        column: PhantomData<C>,
+        alias: &'static str,
+    }
+
+    /// A table declared with `table!`
+    pub trait TypedTable: Copy {
+        /// The table's name
+        const NAME: &'static str;
+    }
+
+    /// A column declared with `table!`, whose values have the Rust type `Type`
+    ///
+    /// Its comparisons only accept values of that type (e.g., `users::karma.gt(10)`).
+    pub trait TypedColumn: Copy {
+        /// The table the column belongs to
+        type Table: TypedTable;
+        /// The Rust type of the column's values
+        type Type: Into<Value>;
+        /// The column's name
+        const NAME: &'static str;
+
+        /// The name the column is qualified by: its table's name, or an alias of the table
+        fn qualifier(self) -> &'static str {
+            Self::Table::NAME
         }
 
-        /// Filter result set based on conditions (`WHERE` clause)
-        pub fn filter(&mut self, expr: &'a str) -> &mut Self {
-            if self.conditions.is_none() {
-                self.conditions = Some(Vec::new());
+        /// The column of the table aliased as `alias` (e.g., `a.karma` in a self-join)
+        fn of(self, alias: &'static str) -> Aliased<Self> {
+            Aliased {
+                column: PhantomData,
+                alias,
             }
+        }
 
-            match self.conditions {
-                Some(ref mut current_conditions) => {
-                    current_conditions.push(expr);
-                }
-                None => unreachable!(),
-            }
+        /// `self = value`
+        fn eq<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Eq, value)
+        }
 
-            self
+        /// `self <> value`
+        fn ne<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Ne, value)
         }
 
-        /// Generate SQL query (`String`) from subsequent method calls
-        pub fn build(&self) -> String {
-            let mut query = String::from("DELETE FROM ");
-            query += self.table;
+        /// `self < value`
+        fn lt<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Lt, value)
+        }
 
-            if let Some(ref conditions) = self.conditions {
-                query += " WHERE ";
-                query += join(conditions, " AND ").as_str();
-            }
+        /// `self <= value`
+        fn le<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Le, value)
+        }
 
-            query += ";";
-            query
+        /// `self > value`
+        fn gt<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Gt, value)
         }
-    }
 
-    impl<'a> Insert<'a> {
-        /// Construct a new `INSERT` query builder
-        pub fn new(table: &'a str) -> Self {
-            Insert {
-                table,
-                values: HashMap::new(),
-                returns: None,
-            }
+        /// `self >= value`
+        fn ge<V: Into<Self::Type>>(self, value: V) -> Expr<'static> {
+            self.compare(Operator::Ge, value)
         }
 
-        /// Set a field value
-        pub fn set(&mut self, field: &'a str, value: &'a str) -> &mut Self {
-            let _ = self.values.insert(field, value);
-            self
+        /// `self LIKE pattern`
+        fn like<V: Into<Self::Type>>(self, pattern: V) -> Expr<'static> {
+            self.compare(Operator::Like, pattern)
         }
 
-        /// Add returning field
-        pub fn returning(&mut self, field: &'a str) -> &mut Self {
-            if self.returns.is_none() {
-                self.returns = Some(Vec::new());
-            }
+        /// `self <op> value`
+        fn compare<V: Into<Self::Type>>(self, op: Operator, value: V) -> Expr<'static> {
+            Expr::from(self).compare(op, typed_value::<Self, V>(value))
+        }
 
-            match self.returns {
-                Some(ref mut current_returns) => {
-                    current_returns.push(field);
-                }
-                None => unreachable!(),
-            }
+        /// `self IN (a, b, ...)`
+        fn is_in<I, V>(self, list: I) -> Expr<'static>
+        where
+            I: IntoIterator<Item = V>,
+            V: Into<Self::Type>,
+        {
+            Expr::from(self).is_in(list.into_iter().map(typed_value::<Self, V>))
+        }
 
-            self
+        /// `self BETWEEN low AND high`
+        fn between<L, H>(self, low: L, high: H) -> Expr<'static>
+        where
+            L: Into<Self::Type>,
+            H: Into<Self::Type>,
+        {
+            let low = typed_value::<Self, L>(low);
+            Expr::from(self).between(low, typed_value::<Self, H>(high))
+        }
+
+        /// `self IS NULL`
+        fn is_null(self) -> Expr<'static> {
+            Expr::from(self).is_null()
         }
 
-        /// Generate SQL query (`String`) from subsequent method calls
-        pub fn build(&self) -> String {
-            let mut query = String::from("INSERT INTO ");
-            query += self.table;
+        /// `self IS NOT NULL`
+        fn is_not_null(self) -> Expr<'static> {
+            Expr::from(self).is_not_null()
+        }
+    }
 
-            let mut columns: Vec<&str> = Vec::with_capacity(self.values.len());
-            let mut values: Vec<&str> = Vec::with_capacity(self.values.len());
+    /// Accumulates SQL text and bound values while a query is generated
+    struct Writer {
+        dialect: Dialect,
+        sql: String,
+        params: Vec<Value>,
+        /// Whether values are written as literals instead of bound (e.g., in `CREATE TABLE`,
+        /// which cannot take parameters)
+        literals: bool,
+    }
 
-            for (field, value) in self.values.iter() {
-                columns.push(field);
-                values.push(value);
-            }
+    /// Reserved words that must be quoted to be used as identifiers, in every dialect
+    const KEYWORDS: &[&str] = &[
+        "ALL",
+        "ALTER",
+        "AND",
+        "ANY",
+        "AS",
+        "ASC",
+        "BETWEEN",
+        "BY",
+        "CASE",
+        "CAST",
+        "CHECK",
+        "COLLATE",
+        "COLUMN",
+        "CONSTRAINT",
+        "CREATE",
+        "CROSS",
+        "CURRENT_DATE",
+        "CURRENT_TIME",
+        "CURRENT_TIMESTAMP",
+        "CURRENT_USER",
+        "DEFAULT",
+        "DELETE",
+        "DESC",
+        "DISTINCT",
+        "DROP",
+        "ELSE",
+        "END",
+        "EXCEPT",
+        "EXISTS",
+        "FALSE",
+        "FETCH",
+        "FOR",
+        "FOREIGN",
+        "FROM",
+        "FULL",
+        "GRANT",
+        "GROUP",
+        "HAVING",
+        "IN",
+        "INDEX",
+        "INNER",
+        "INSERT",
+        "INTERSECT",
+        "INTO",
+        "IS",
+        "JOIN",
+        "KEY",
+        "LATERAL",
+        "LEFT",
+        "LIKE",
+        "LIMIT",
+        "NATURAL",
+        "NOT",
+        "NULL",
+        "OFFSET",
+        "ON",
+        "OR",
+        "ORDER",
+        "OUTER",
+        "PRIMARY",
+        "REFERENCES",
+        "RIGHT",
+        "ROW",
+        "ROWS",
+        "SELECT",
+        "SESSION_USER",
+        "SET",
+        "SOME",
+        "TABLE",
+        "THEN",
+        "TO",
+        "TRUE",
+        "UNION",
+        "UNIQUE",
+        "UPDATE",
+        "USER",
+        "USING",
+        "VALUES",
+        "WHEN",
+        "WHERE",
+        "WINDOW",
+        "WITH",
+    ];
 
-            query += " (";
-            query += join(&columns, ", ").as_str();
-            query += ") VALUES (";
-            query += join(&values, ", ").as_str();
-            query += ")";
+    /// Other words that PostgreSQL reserves
+    const KEYWORDS_POSTGRES: &[&str] = &[
+        "ANALYSE",
+        "ANALYZE",
+        "ARRAY",
+        "ASYMMETRIC",
+        "AUTHORIZATION",
+        "BINARY",
+        "BOTH",
+        "COLLATION",
+        "CONCURRENTLY",
+        "CURRENT_CATALOG",
+        "CURRENT_ROLE",
+        "CURRENT_SCHEMA",
+        "DEFERRABLE",
+        "DO",
+        "FREEZE",
+        "ILIKE",
+        "INITIALLY",
+        "ISNULL",
+        "LEADING",
+        "LOCALTIME",
+        "LOCALTIMESTAMP",
+        "NOTNULL",
+        "ONLY",
+        "OVERLAPS",
+        "PLACING",
+        "RETURNING",
+        "SIMILAR",
+        "SYMMETRIC",
+        "SYSTEM_USER",
+        "TABLESAMPLE",
+        "TRAILING",
+        "VARIADIC",
+        "VERBOSE",
+    ];
 
-            if let Some(ref returns) = self.returns {
-                query += " RETURNING ";
-                query += join(returns, ", ").as_str();
-            }
+    /// Other words that MySQL reserves
+    const KEYWORDS_MYSQL: &[&str] = &[
+        "ACCESSIBLE",
+        "ADD",
+        "ANALYZE",
+        "ASENSITIVE",
+        "BEFORE",
+        "BIGINT",
+        "BINARY",
+        "BLOB",
+        "BOTH",
+        "CALL",
+        "CASCADE",
+        "CHANGE",
+        "CHAR",
+        "CHARACTER",
+        "CONDITION",
+        "CONTINUE",
+        "CONVERT",
+        "CUBE",
+        "CUME_DIST",
+        "CURSOR",
+        "DATABASE",
+        "DATABASES",
+        "DAY_HOUR",
+        "DAY_MICROSECOND",
+        "DAY_MINUTE",
+        "DAY_SECOND",
+        "DEC",
+        "DECIMAL",
+        "DECLARE",
+        "DELAYED",
+        "DENSE_RANK",
+        "DESCRIBE",
+        "DETERMINISTIC",
+        "DISTINCTROW",
+        "DIV",
+        "DOUBLE",
+        "DUAL",
+        "EACH",
+        "ELSEIF",
+        "EMPTY",
+        "ENCLOSED",
+        "ESCAPED",
+        "EXIT",
+        "EXPLAIN",
+        "FIRST_VALUE",
+        "FLOAT",
+        "FLOAT4",
+        "FLOAT8",
+        "FORCE",
+        "FULLTEXT",
+        "FUNCTION",
+        "GENERATED",
+        "GET",
+        "GROUPING",
+        "GROUPS",
+        "HIGH_PRIORITY",
+        "HOUR_MICROSECOND",
+        "HOUR_MINUTE",
+        "HOUR_SECOND",
+        "IF",
+        "IGNORE",
+        "INFILE",
+        "INOUT",
+        "INSENSITIVE",
+        "INT",
+        "INT1",
+        "INT2",
+        "INT3",
+        "INT4",
+        "INT8",
+        "INTEGER",
+        "INTERVAL",
+        "IO_AFTER_GTIDS",
+        "IO_BEFORE_GTIDS",
+        "ITERATE",
+        "JSON_TABLE",
+        "KEYS",
+        "KILL",
+        "LAG",
+        "LAST_VALUE",
+        "LEAD",
+        "LEADING",
+        "LEAVE",
+        "LINEAR",
+        "LINES",
+        "LOAD",
+        "LOCALTIME",
+        "LOCALTIMESTAMP",
+        "LOCK",
+        "LONG",
+        "LONGBLOB",
+        "LONGTEXT",
+        "LOOP",
+        "LOW_PRIORITY",
+        "MANUAL",
+        "MASTER_BIND",
+        "MASTER_SSL_VERIFY_SERVER_CERT",
+        "MATCH",
+        "MAXVALUE",
+        "MEDIUMBLOB",
+        "MEDIUMINT",
+        "MEDIUMTEXT",
+        "MIDDLEINT",
+        "MINUTE_MICROSECOND",
+        "MINUTE_SECOND",
+        "MOD",
+        "MODIFIES",
+        "NO_WRITE_TO_BINLOG",
+        "NTH_VALUE",
+        "NTILE",
+        "NUMERIC",
+        "OF",
+        "OPTIMIZE",
+        "OPTIMIZER_COSTS",
+        "OPTION",
+        "OPTIONALLY",
+        "OUT",
+        "OUTFILE",
+        "OVER",
+        "PARALLEL",
+        "PARTITION",
+        "PERCENT_RANK",
+        "PRECISION",
+        "PROCEDURE",
+        "PURGE",
+        "QUALIFY",
+        "RANGE",
+        "RANK",
+        "READ",
+        "READS",
+        "READ_WRITE",
+        "REAL",
+        "RECURSIVE",
+        "REGEXP",
+        "RELEASE",
+        "RENAME",
+        "REPEAT",
+        "REPLACE",
+        "REQUIRE",
+        "RESIGNAL",
+        "RESTRICT",
+        "RETURN",
+        "REVOKE",
+        "RLIKE",
+        "ROW_NUMBER",
+        "SCHEMA",
+        "SCHEMAS",
+        "SECOND_MICROSECOND",
+        "SENSITIVE",
+        "SEPARATOR",
+        "SHOW",
+        "SIGNAL",
+        "SMALLINT",
+        "SPATIAL",
+        "SPECIFIC",
+        "SQL",
+        "SQLEXCEPTION",
+        "SQLSTATE",
+        "SQLWARNING",
+        "SQL_BIG_RESULT",
+        "SQL_CALC_FOUND_ROWS",
+        "SQL_SMALL_RESULT",
+        "SSL",
+        "STARTING",
+        "STORED",
+        "STRAIGHT_JOIN",
+        "SYSTEM",
+        "TABLESAMPLE",
+        "TERMINATED",
+        "TINYBLOB",
+        "TINYINT",
+        "TINYTEXT",
+        "TRAILING",
+        "TRIGGER",
+        "UNDO",
+        "UNLOCK",
+        "UNSIGNED",
+        "USAGE",
+        "USE",
+        "UTC_DATE",
+        "UTC_TIME",
+        "UTC_TIMESTAMP",
+        "VARBINARY",
+        "VARCHAR",
+        "VARCHARACTER",
+        "VARYING",
+        "VIRTUAL",
+        "WHILE",
+        "WRITE",
+        "XOR",
+        "YEAR_MONTH",
+        "ZEROFILL",
+    ];
 
-            query += ";";
+    /// Other keywords of SQLite, which it recommends quoting when used as identifiers
+    const KEYWORDS_SQLITE: &[&str] = &[
+        "ABORT",
+        "ACTION",
+        "ADD",
+        "AFTER",
+        "ALWAYS",
+        "ANALYZE",
+        "ATTACH",
+        "AUTOINCREMENT",
+        "BEFORE",
+        "BEGIN",
+        "CASCADE",
+        "COMMIT",
+        "CONFLICT",
+        "CURRENT",
+        "DATABASE",
+        "DEFERRABLE",
+        "DEFERRED",
+        "DETACH",
+        "DO",
+        "EACH",
+        "ESCAPE",
+        "EXCLUDE",
+        "EXCLUSIVE",
+        "EXPLAIN",
+        "FAIL",
+        "FILTER",
+        "FIRST",
+        "FOLLOWING",
+        "GENERATED",
+        "GLOB",
+        "GROUPS",
+        "IF",
+        "IGNORE",
+        "IMMEDIATE",
+        "INDEXED",
+        "INITIALLY",
+        "INSTEAD",
+        "ISNULL",
+        "LAST",
+        "MATCH",
+        "MATERIALIZED",
+        "NO",
+        "NOTHING",
+        "NOTNULL",
+        "NULLS",
+        "OF",
+        "OTHERS",
+        "OVER",
+        "PARTITION",
+        "PLAN",
+        "PRAGMA",
+        "PRECEDING",
+        "QUERY",
+        "RAISE",
+        "RANGE",
+        "RECURSIVE",
+        "REGEXP",
+        "REINDEX",
+        "RELEASE",
+        "RENAME",
+        "REPLACE",
+        "RESTRICT",
+        "RETURNING",
+        "ROLLBACK",
+        "SAVEPOINT",
+        "TEMP",
+        "TEMPORARY",
+        "TIES",
+        "TRANSACTION",
+        "TRIGGER",
+        "UNBOUNDED",
+        "VACUUM",
+        "VIEW",
+        "VIRTUAL",
+        "WITHOUT",
+    ];
 
-            query
-        }
+    /// Whether `name` consists only of letters, digits and underscores, not starting with a digit
+    fn is_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        let starts_with_letter = match chars.next() {
+            Some(c) => c.is_ascii_alphabetic() || c == '_',
+            None => false,
+        };
+
+        starts_with_letter && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
     }
 
-    impl<'a> Select<'a> {
-        /// Construct a new `SELECT` query builder
-        pub fn new(table: &'a str) -> Self {
-            Select {
-                table,
-                aliases: None,
-                fields: None,
-                order: None,
-                joins: None,
-                conditions: None,
-                groupings: None,
-                havings: None,
-                limit: 0usize,
-                offset: 0usize,
+    /// Whether `path` consists of `.`-separated names, optionally ending in `*`
+    fn is_path(path: &str) -> bool {
+        let mut parts = path.split('.').peekable();
+        while let Some(part) = parts.next() {
+            let last = parts.peek().is_none();
+            if !(is_name(part) || (last && part == "*")) {
+                return false;
             }
         }
+        true
+    }
 
-        /// Set a table alias (`AS`)
-        pub fn alias(&mut self, table: &'a str, alias: &'a str) -> &mut Self {
-            if self.aliases.is_none() {
-                self.aliases = Some(HashMap::new());
+    /// Byte offsets of the `?` markers in `sql`, skipping quoted literals and identifiers
+    fn markers(sql: &str) -> Vec<usize> {
+        let mut quote = None;
+        let mut markers = Vec::new();
+        for (i, c) in sql.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '\'' || c == '"' || c == '`' => quote = Some(c),
+                None if c == '?' => markers.push(i),
+                None => {}
             }
+        }
+        markers
+    }
 
-            match self.aliases {
-                Some(ref mut aliases) => {
-                    aliases.insert(table, alias);
-                }
-                None => unreachable!(),
-            }
+    /// Whether `name` is a word that every dialect reserves (e.g., `TRUE` or `NULL`)
+    fn is_keyword(name: &str) -> bool {
+        KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(name))
+    }
 
-            self
+    /// Binding strength of verbatim SQL, which is unknown and so assumed to be the weakest
+    const PRECEDENCE_RAW: u8 = 0;
+    /// Binding strength of `OR`
+    const PRECEDENCE_OR: u8 = 1;
+    /// Binding strength of `AND`
+    const PRECEDENCE_AND: u8 = 2;
+    /// Binding strength of comparisons and other predicates
+    const PRECEDENCE_PREDICATE: u8 = 3;
+    /// Binding strength of columns, values, and subqueries
+    const PRECEDENCE_ATOM: u8 = 4;
+
+    impl<'a> Expr<'a> {
+        /// `self = other`
+        pub fn eq<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Eq, other)
         }
 
-        /// Specify desired table fields in result set
-        pub fn fields(&mut self, fields: &[&'a str]) -> &mut Self {
-            if self.fields.is_none() {
-                self.fields = Some(Vec::new());
-            }
+        /// `self <> other`
+        pub fn ne<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Ne, other)
+        }
 
-            match self.fields {
-                Some(ref mut current_fields) => {
-                    for field in fields {
-                        current_fields.push(field);
-                    }
-                }
-                None => unreachable!(),
-            }
+        /// `self < other`
+        pub fn lt<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Lt, other)
+        }
 
-            self
+        /// `self <= other`
+        pub fn le<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Le, other)
         }
 
-        /// Filter result set based on conditions (`WHERE` clause)
-        pub fn filter(&mut self, expr: &'a str) -> &mut Self {
-            if self.conditions.is_none() {
-                self.conditions = Some(Vec::new());
-            }
+        /// `self > other`
+        pub fn gt<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Gt, other)
+        }
 
-            match self.conditions {
-                Some(ref mut current_conditions) => {
-                    current_conditions.push(expr);
-                }
-                None => unreachable!(),
-            }
+        /// `self >= other`
+        pub fn ge<O: Operand<'a>>(self, other: O) -> Self {
+            self.compare(Operator::Ge, other)
+        }
 
-            self
+        /// `self LIKE pattern`
+        pub fn like<O: Operand<'a>>(self, pattern: O) -> Self {
+            self.compare(Operator::Like, pattern)
         }
 
-        /// Group result set based on common value (`GROUP BY` clause)
-        pub fn group_by(&mut self, val: &'a str) -> &mut Self {
-            if self.groupings.is_none() {
-                self.groupings = Some(Vec::new());
-            }
+        /// `self NOT LIKE pattern`
+        pub fn not_like<O: Operand<'a>>(self, pattern: O) -> Self {
+            self.compare(Operator::NotLike, pattern)
+        }
 
-            match self.groupings {
-                Some(ref mut current_groupings) => {
-                    current_groupings.push(val);
-                }
-                None => unreachable!(),
-            }
+        /// `self <op> other`
+        pub fn compare<O: Operand<'a>>(self, op: Operator, other: O) -> Self {
+            Expr::Compare(Box::new(self), op, Box::new(other.into_expr()))
+        }
 
-            self
+        /// `self IN (list...)`
+        pub fn is_in<I, O>(self, list: I) -> Self
+        where
+            I: IntoIterator<Item = O>,
+            O: Operand<'a>,
+        {
+            Expr::In(
+                Box::new(self),
+                list.into_iter().map(Operand::into_expr).collect(),
+            )
         }
 
-        /// Filter result set based on an expression on an aggregate value (`HAVING` clause)
-        pub fn having(&mut self, expr: &'a str) -> &mut Self {
-            if self.havings.is_none() {
-                self.havings = Some(Vec::new());
+        /// `self IN (SELECT ...)`
+        pub fn in_subquery(self, query: Select<'a>) -> Self {
+            Expr::InSubquery(Box::new(self), Box::new(query))
+        }
+
+        /// `self BETWEEN low AND high`
+        pub fn between<L: Operand<'a>, H: Operand<'a>>(self, low: L, high: H) -> Self {
+            Expr::Between(
+                Box::new(self),
+                Box::new(low.into_expr()),
+                Box::new(high.into_expr()),
+            )
+        }
+
+        /// `self IS NULL`
+        pub fn is_null(self) -> Self {
+            Expr::IsNull(Box::new(self))
+        }
+
+        /// `self IS NOT NULL`
+        pub fn is_not_null(self) -> Self {
+            Expr::IsNotNull(Box::new(self))
+        }
+
+        /// `self AND other`
+        pub fn and<E: Into<Expr<'a>>>(self, other: E) -> Self {
+            match self {
+                Expr::And(mut exprs) => {
+                    exprs.push(other.into());
+                    Expr::And(exprs)
+                }
+                expr => Expr::And(vec![expr, other.into()]),
             }
+        }
 
-            match self.havings {
-                Some(ref mut current_havings) => {
-                    current_havings.push(expr);
+        /// `self OR other`
+        pub fn or<E: Into<Expr<'a>>>(self, other: E) -> Self {
+            match self {
+                Expr::Or(mut exprs) => {
+                    exprs.push(other.into());
+                    Expr::Or(exprs)
                 }
-                None => unreachable!(),
+                expr => Expr::Or(vec![expr, other.into()]),
             }
+        }
 
-            self
+        /// Evaluate a window function over a window of rows (`self OVER (...)`)
+        pub fn over(self, window: Window<'a>) -> Self {
+            Expr::Over(Box::new(self), Box::new(window))
         }
 
-        /// Order result set based on the value of an expression (`ORDER BY` clause)
-        pub fn order_by(&mut self, expr: &'a str, direction: Order) -> &mut Self {
-            if self.order.is_none() {
-                self.order = Some(Vec::new());
-            }
+        /// The expression's SQL and bound values, for comparing expressions
+        fn to_sql(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            query.push_expr(self)?;
+            Ok((query.sql, query.params))
+        }
 
-            match self.order {
-                Some(ref mut current_order) => {
-                    let order = (expr, direction);
-                    current_order.push(order);
+        /// How tightly the expression binds, when it is an operand of another expression
+        fn precedence(&self) -> u8 {
+            match *self {
+                Expr::And(ref exprs) | Expr::Or(ref exprs) if exprs.len() == 1 => {
+                    exprs[0].precedence()
                 }
-                None => unreachable!(),
+                Expr::And(ref exprs) | Expr::Or(ref exprs) if exprs.is_empty() => {
+                    PRECEDENCE_PREDICATE
+                }
+                Expr::Or(_) => PRECEDENCE_OR,
+                Expr::And(_) => PRECEDENCE_AND,
+                Expr::Not(_)
+                | Expr::Compare(..)
+                | Expr::In(..)
+                | Expr::InSubquery(..)
+                | Expr::Between(..)
+                | Expr::IsNull(_)
+                | Expr::IsNotNull(_) => PRECEDENCE_PREDICATE,
+                Expr::Raw(_) | Expr::Bound(..) => PRECEDENCE_RAW,
+                Expr::Column(_)
+                | Expr::Value(_)
+                | Expr::Exists(_)
+                | Expr::Subquery(_)
+                | Expr::Excluded(_)
+                | Expr::Over(..) => PRECEDENCE_ATOM,
             }
-
-            self
         }
+    }
 
-        pub fn join(
-            &mut self,
-            table: &'a str,
-            on_left: &'a str,
-            on_right: &'a str,
-            kind: Join,
-        ) -> &mut Self {
-            if self.joins.is_none() {
-                self.joins = Some(Vec::new());
+    /// Interpret `expr` as a column if it is a (possibly qualified) name, or as verbatim SQL
+    /// otherwise (including a single reserved word, e.g., `TRUE`, `NULL` or `CURRENT_DATE`)
+    impl<'a> From<Cow<'a, str>> for Expr<'a> {
+        fn from(expr: Cow<'a, str>) -> Self {
+            if is_path(&expr) && !is_keyword(&expr) {
+                Expr::Column(Ident::path(expr))
+            } else {
+                Expr::Raw(expr)
             }
+        }
+    }
 
-            match self.joins {
-                Some(ref mut current_joins) => {
-                    let join = JoinClause {
-                        table,
-                        on_left,
-                        on_right,
-                        kind,
-                    };
-                    current_joins.push(join);
-                }
-                None => unreachable!(),
-            }
+    impl<'a> From<&'a str> for Expr<'a> {
+        fn from(expr: &'a str) -> Self {
+            Expr::from(Cow::Borrowed(expr))
+        }
+    }
 
-            self
+    impl<'a> From<String> for Expr<'a> {
+        fn from(expr: String) -> Self {
+            Expr::from(Cow::Owned(expr))
         }
+    }
 
-        /// Limit number of rows in result set (`LIMIT`)
-        pub fn limit(&mut self, limit: usize) -> &mut Self {
-            self.limit = limit;
-            self
+    impl<'a> From<Ident<'a>> for Expr<'a> {
+        fn from(ident: Ident<'a>) -> Self {
+            Expr::Column(ident)
         }
+    }
 
-        /// Offset number of rows in result set (`OFFSET`)
-        pub fn offset(&mut self, offset: usize) -> &mut Self {
-            self.offset = offset;
+    /// Qualify the column by its table's name (e.g., `users.id`), so it cannot be confused with
+    /// a column of a joined table
+    impl<'a, C: TypedColumn> From<C> for Expr<'a> {
+        fn from(column: C) -> Self {
+            Expr::Column(Ident {
+                parts: vec![Cow::Borrowed(column.qualifier()), Cow::Borrowed(C::NAME)],
+            })
+        }
+    }
+
+    impl<C: TypedColumn> TypedColumn for Aliased<C> {
+        type Table = C::Table;
+        type Type = C::Type;
+        const NAME: &'static str = C::NAME;
+
+        fn qualifier(self) -> &'static str {
+            self.alias
+        }
+    }
+
+    impl<'a> Operand<'a> for Expr<'a> {
+        fn into_expr(self) -> Expr<'a> {
             self
         }
+    }
 
-        /// Generate SQL query (`String`) from subsequent method calls
-        pub fn build(&self) -> String {
-            let mut query = String::from("SELECT ");
+    impl<'a, V: Into<Value>> Operand<'a> for V {
+        fn into_expr(self) -> Expr<'a> {
+            Expr::Value(self.into())
+        }
+    }
 
-            match self.fields {
-                Some(ref fields) => {
-                    query += join(fields, ", ").as_str();
-                }
-                None => query += "*",
+    impl Operator {
+        fn as_str(self) -> &'static str {
+            match self {
+                Operator::Eq => " = ",
+                Operator::Ne => " <> ",
+                Operator::Lt => " < ",
+                Operator::Le => " <= ",
+                Operator::Gt => " > ",
+                Operator::Ge => " >= ",
+                Operator::Like => " LIKE ",
+                Operator::NotLike => " NOT LIKE ",
             }
+        }
+    }
 
-            query += " FROM ";
-            query += self.table;
+    impl<'a> Assignments<'a> {
+        /// Set `field` to `value`, keeping its position if it is already set
+        fn set(&mut self, field: Ident<'a>, value: Expr<'a>) {
+            match self.0.iter().position(|(f, _)| *f == field) {
+                Some(i) => self.0[i].1 = value,
+                None => self.0.push((field, value)),
+            }
+        }
 
-            if let Some(ref aliases) = self.aliases {
-                if let Some(ref alias) = aliases.get(self.table) {
-                    query += " AS ";
-                    query += *alias;
-                }
+        /// Remove `field`, if it is set
+        fn unset(&mut self, field: &Ident<'a>) {
+            self.0.retain(|(f, _)| f != field);
+        }
+
+        /// Values in the order of `columns`, if exactly those fields are set
+        fn ordered_by<'b>(&'b self, columns: &[&Ident<'a>]) -> Option<Vec<&'b Expr<'a>>> {
+            if self.0.len() != columns.len() {
+                return None;
             }
 
-            if let Some(ref joins) = self.joins {
-                for join in joins.iter() {
-                    match join.kind {
-                        Join::Left => query += " LEFT",
-                        Join::Inner => query += " INNER",
-                    }
+            columns
+                .iter()
+                .map(|column| {
+                    self.0
+                        .iter()
+                        .find(|(f, _)| f == *column)
+                        .map(|(_, value)| value)
+                })
+                .collect()
+        }
 
-                    query += " JOIN ";
-                    query += join.table;
+        /// Move `fields` to the front, in the given order; other fields keep their relative order
+        fn reorder(&mut self, fields: &[Ident<'a>]) {
+            let rank = |field: &Ident<'a>| {
+                fields
+                    .iter()
+                    .position(|f| f == field)
+                    .unwrap_or(fields.len())
+            };
+            self.0.sort_by_key(|(f, _)| rank(f));
+        }
+    }
 
-                    if let Some(ref aliases) = self.aliases {
-                        if let Some(ref alias) = aliases.get(join.table) {
-                            query += " AS ";
-                            query += *alias;
-                        }
+    impl<'a> InsertRow<'a> {
+        /// Values of the row in the order of `columns`, if it supplies exactly those columns
+        fn values<'b>(&'b self, columns: &[&Ident<'a>]) -> Option<Vec<&'b Expr<'a>>> {
+            match *self {
+                InsertRow::Positional(ref values) => {
+                    if values.len() == columns.len() {
+                        Some(values.iter().collect())
+                    } else {
+                        None
                     }
-
-                    query += " ON ";
-                    query += join.on_left;
-                    query += " = ";
-                    query += join.on_right;
                 }
+                InsertRow::Named(ref values) => values.ordered_by(columns),
             }
+        }
+    }
 
-            if let Some(ref conditions) = self.conditions {
-                query += " WHERE ";
-                query += join(conditions, " AND ").as_str();
-            }
-
-            if let Some(ref groupings) = self.groupings {
-                query += " GROUP BY ";
-                query += join(groupings, ", ").as_str();
-            }
+    /// Floats are compared by their bits (with `-0.0` treated as `0.0`), so that values can be
+    /// used as keys and `NaN` equals itself
+    fn float_bits(v: f64) -> u64 {
+        if v == 0.0 {
+            0.0f64.to_bits()
+        } else {
+            v.to_bits()
+        }
+    }
 
-            if let Some(ref havings) = self.havings {
-                query += " HAVING ";
-                query += join(havings, " AND ").as_str();
+    impl PartialEq for Value {
+        fn eq(&self, other: &Value) -> bool {
+            match (self, other) {
+                (Value::Null, Value::Null) => true,
+                (Value::Bool(a), Value::Bool(b)) => a == b,
+                (Value::Int(a), Value::Int(b)) => a == b,
+                (Value::Float(a), Value::Float(b)) => float_bits(*a) == float_bits(*b),
+                (Value::Text(a), Value::Text(b)) => a == b,
+                (Value::Bytes(a), Value::Bytes(b)) => a == b,
+                _ => false,
             }
+        }
+    }
 
-            if let Some(ref order) = self.order {
-                query += " ORDER BY ";
-                for item in order.iter() {
-                    let (ref expr, ref dir) = *item;
-                    query += expr;
-                    match *dir {
-                        Order::Asc => query += " ASC",
-                        Order::Desc => query += " DESC",
-                    }
-                }
-            }
+    impl Eq for Value {}
 
-            if self.limit != 0 {
-                query += " LIMIT ";
-                query += self.limit.to_string().as_str();
+    impl Hash for Value {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            ::std::mem::discriminant(self).hash(state);
+            match *self {
+                Value::Null => {}
+                Value::Bool(v) => v.hash(state),
+                Value::Int(v) => v.hash(state),
+                Value::Float(v) => float_bits(v).hash(state),
+                Value::Text(ref v) => v.hash(state),
+                Value::Bytes(ref v) => v.hash(state),
             }
+        }
+    }
 
-            if self.offset != 0 {
-                query += " OFFSET ";
-                query += self.offset.to_string().as_str();
+    impl fmt::Display for Dialect {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Dialect::Postgres => write!(f, "PostgreSQL"),
+                Dialect::MySql => write!(f, "MySQL"),
+                Dialect::Sqlite => write!(f, "SQLite"),
             }
-
-            query += ";";
-            query
         }
     }
 
-    impl<'a> Update<'a> {
-        /// Construct a new `UPDATE` query builder
-        pub fn new(table: &'a str) -> Self {
-            Update {
-                table,
-                values: HashMap::new(),
-                conditions: None,
-                returns: None,
-            }
+    impl<'a> Window<'a> {
+        /// Construct a new window of all rows
+        pub fn new() -> Self {
+            Window::default()
         }
 
-        /// Set a field value
-        pub fn set(&mut self, field: &'a str, value: &'a str) -> &mut Self {
-            let _ = self.values.insert(field, value);
-            self
+        /// Construct a new window that extends a named window of the query's `WINDOW` clause
+        pub fn named<S: Into<Cow<'a, str>>>(name: S) -> Self {
+            Window {
+                base: Some(Ident::new(name)),
+                ..Window::default()
+            }
         }
 
-        /// Add returning field
-        pub fn returning(&mut self, field: &'a str) -> &mut Self {
-            if self.returns.is_none() {
-                self.returns = Some(Vec::new());
+        /// Divide rows into groups that share a common value (`PARTITION BY` clause)
+        pub fn partition_by<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            if self.partitions.is_none() {
+                self.partitions = Some(Vec::new());
             }
 
-            match self.returns {
-                Some(ref mut current_returns) => {
-                    current_returns.push(field);
+            match self.partitions {
+                Some(ref mut current_partitions) => {
+                    current_partitions.push(expr.into());
                 }
                 None => unreachable!(),
             }
@@ -479,15 +1569,20 @@ pub mod query_builder {
             self
         }
 
-        /// Filter result set based on conditions (`WHERE` clause)
-        pub fn filter(&mut self, expr: &'a str) -> &mut Self {
-            if self.conditions.is_none() {
-                self.conditions = Some(Vec::new());
+        /// Order rows based on the value of an expression (`ORDER BY` clause)
+        pub fn order_by<E: Into<Expr<'a>>>(&mut self, expr: E, direction: Order) -> &mut Self {
+            self.order_by_key(OrderBy::new(expr, direction))
+        }
+
+        /// Order rows by a sort key, with its options (`ORDER BY` clause)
+        pub fn order_by_key(&mut self, key: OrderBy<'a>) -> &mut Self {
+            if self.order.is_none() {
+                self.order = Some(Vec::new());
             }
 
-            match self.conditions {
-                Some(ref mut current_conditions) => {
-                    current_conditions.push(expr);
+            match self.order {
+                Some(ref mut current_order) => {
+                    current_order.push(key);
                 }
                 None => unreachable!(),
             }
@@ -495,222 +1590,7165 @@ pub mod query_builder {
             self
         }
 
-        /// Generate SQL query (`String`) from subsequent method calls
-        pub fn build(&self) -> String {
-            let mut query = String::from("UPDATE ");
-            query += self.table;
+        /// Limit rows to a frame around the current row, counted in rows (`ROWS BETWEEN`)
+        pub fn rows(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+            self.frame = Some((FrameUnits::Rows, start, end));
+            self
+        }
 
-            let assignments: Vec<String>;
-            assignments = self
-                .values
-                .iter()
-                .map(|(&field, &value)| {
-                    let mut assignment = String::from(field);
-                    assignment += " = ";
-                    assignment += value;
-                    assignment
-                })
-                .collect();
+        /// Limit rows to a frame around the current row, by the value of the `ORDER BY`
+        /// expression (`RANGE BETWEEN`)
+        pub fn range(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+            self.frame = Some((FrameUnits::Range, start, end));
+            self
+        }
 
-            query += " SET ";
-            query += assignments.join(" AND ").as_str();
+        /// Whether the window is just a reference to a named window
+        fn is_named(&self) -> bool {
+            self.base.is_some()
+                && self.partitions.is_none()
+                && self.order.is_none()
+                && self.frame.is_none()
+        }
+    }
 
-            if let Some(ref conditions) = self.conditions {
-                query += " WHERE ";
-                query += join(conditions, " AND ").as_str();
+    impl<'a> Lock<'a> {
+        /// Construct a new lock on the rows of all tables in the query
+        pub fn new(strength: LockStrength) -> Self {
+            Lock {
+                strength,
+                tables: None,
+                wait: LockWait::Wait,
             }
+        }
 
-            if let Some(ref returns) = self.returns {
-                query += " RETURNING ";
-                query += join(returns, ", ").as_str();
-            }
+        /// Only lock the rows of the given tables (`OF`)
+        pub fn of(mut self, tables: &[&'a str]) -> Self {
+            self.tables = Some(tables.iter().map(|t| Ident::from(*t)).collect());
+            self
+        }
 
-            query += ";";
-            query
+        /// Fail instead of waiting for rows locked by another transaction (`NOWAIT`)
+        pub fn nowait(mut self) -> Self {
+            self.wait = LockWait::NoWait;
+            self
+        }
+
+        /// Leave rows locked by another transaction out of the result set (`SKIP LOCKED`)
+        pub fn skip_locked(mut self) -> Self {
+            self.wait = LockWait::SkipLocked;
+            self
         }
     }
 
-    /// Helper function to construct new `DELETE` query builder
-    pub fn delete(table: &str) -> Delete {
-        Delete::new(table)
+    impl<'a> Scope<'a> {
+        /// Construct a new, empty scope
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause)
+        pub fn filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.conditions.push(expr.into());
+            self
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause), binding each `?` in `expr`
+        /// to the corresponding value
+        ///
+        /// Building the query returns an error if the number of `?` markers in `expr` is not equal
+        /// to the number of `values`.
+        pub fn filter_with<S: Into<Cow<'a, str>>>(
+            &mut self,
+            expr: S,
+            values: Vec<Value>,
+        ) -> &mut Self {
+            self.conditions.push(Expr::Bound(expr.into(), values));
+            self
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on the equality of two expressions
+        pub fn join<T, L, R>(&mut self, table: T, on_left: L, on_right: R, kind: Join) -> &mut Self
+        where
+            T: Into<Table<'a>>,
+            L: Into<Expr<'a>>,
+            R: Into<Expr<'a>>,
+        {
+            let condition = on_left.into().eq(on_right.into());
+            self.push_join(table.into(), JoinConstraint::On(condition), kind)
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on an arbitrary condition
+        pub fn join_on<T: Into<Table<'a>>, E: Into<Expr<'a>>>(
+            &mut self,
+            table: T,
+            condition: E,
+            kind: Join,
+        ) -> &mut Self {
+            self.push_join(table.into(), JoinConstraint::On(condition.into()), kind)
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on the equality of columns that both
+        /// tables have (`USING`)
+        pub fn join_using<T: Into<Table<'a>>>(
+            &mut self,
+            table: T,
+            columns: &[&'a str],
+            kind: Join,
+        ) -> &mut Self {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.push_join(table.into(), JoinConstraint::Using(columns), kind)
+        }
+
+        fn push_join(
+            &mut self,
+            table: Table<'a>,
+            constraint: JoinConstraint<'a>,
+            kind: Join,
+        ) -> &mut Self {
+            self.joins.push(JoinClause {
+                table,
+                constraint,
+                kind,
+            });
+            self
+        }
+
+        /// Order result set based on the value of an expression (`ORDER BY` clause)
+        pub fn order_by<E: Into<Expr<'a>>>(&mut self, expr: E, direction: Order) -> &mut Self {
+            self.order_by_key(OrderBy::new(expr, direction))
+        }
+
+        /// Order result set by a sort key, with its options (`ORDER BY` clause)
+        pub fn order_by_key(&mut self, key: OrderBy<'a>) -> &mut Self {
+            self.order.push(key);
+            self
+        }
     }
 
-    /// Helper function to construct new `INSERT` query builder
-    pub fn insert(table: &str) -> Insert {
-        Insert::new(table)
+    impl<'a> IntoScope<'a> for Scope<'a> {
+        fn into_scope(self) -> Scope<'a> {
+            self
+        }
     }
 
-    /// Helper function to construct new `SELECT` query builder
-    pub fn select(table: &str) -> Select {
-        Select::new(table)
+    impl<'a> IntoScope<'a> for &Scope<'a> {
+        fn into_scope(self) -> Scope<'a> {
+            self.clone()
+        }
     }
 
-    /// Helper function to construct new `UPDATE` query builder
-    pub fn update(table: &str) -> Update {
-        Update::new(table)
+    impl<'a> IntoScope<'a> for &mut Scope<'a> {
+        fn into_scope(self) -> Scope<'a> {
+            self.clone()
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::query_builder;
+    impl<'a> Column<'a> {
+        /// Construct a new column definition, which allows `NULL` values
+        pub fn new<N: Into<Ident<'a>>>(name: N, data_type: DataType<'a>) -> Self {
+            Column {
+                name: name.into(),
+                data_type,
+                not_null: false,
+                default: None,
+                auto_increment: false,
+                primary_key: false,
+                unique: false,
+                checks: Vec::new(),
+                references: None,
+            }
+        }
 
-    #[test]
-    fn test_debug() {
-        let query_builder = query_builder::select("users");
-        let query = format!("{:?}", query_builder);
-        assert_eq!(
-            "Select { table: \"users\", aliases: None, fields: None, order: None, \
-             joins: None, groupings: None, havings: None, conditions: None, limit: 0, offset: 0 }",
-            query
-        );
-    }
+        /// Reject `NULL` values (`NOT NULL`)
+        pub fn not_null(mut self) -> Self {
+            self.not_null = true;
+            self
+        }
 
-    #[test]
-    fn test_display() {
-        let query_builder = query_builder::select("users");
-        let query = format!("{}", query_builder);
-        assert_eq!("SELECT * FROM users;", query);
-    }
+        /// Use a value (or an expression, used as-is) when a row does not supply one (`DEFAULT`)
+        pub fn default<V: Operand<'a>>(mut self, value: V) -> Self {
+            self.default = Some(value.into_expr());
+            self
+        }
 
-    #[test]
-    fn test_delete_query() {
-        let query = query_builder::delete("users").build();
-        assert_eq!("DELETE FROM users;", query);
-    }
+        /// Assign increasing integers to rows that do not supply a value (e.g.,
+        /// `GENERATED BY DEFAULT AS IDENTITY` or `AUTO_INCREMENT`)
+        ///
+        /// On SQLite, the column must also be the primary key, and is written as
+        /// `INTEGER PRIMARY KEY`.
+        pub fn auto_increment(mut self) -> Self {
+            self.auto_increment = true;
+            self
+        }
 
-    #[test]
-    fn test_delete_query_with_conditions() {
-        let query = query_builder::delete("users")
-            .filter("name = $1")
-            .filter("karma <= $2")
-            .build();
-        assert_eq!("DELETE FROM users WHERE name = $1 AND karma <= $2;", query);
+        /// Identify rows by the column's values (`PRIMARY KEY`)
+        pub fn primary_key(mut self) -> Self {
+            self.primary_key = true;
+            self
+        }
+
+        /// Require rows to have distinct values (`UNIQUE`)
+        pub fn unique(mut self) -> Self {
+            self.unique = true;
+            self
+        }
+
+        /// Require values to meet a condition (`CHECK`)
+        pub fn check<E: Into<Expr<'a>>>(mut self, expr: E) -> Self {
+            self.checks.push(expr.into());
+            self
+        }
+
+        /// Require values to match a row of another table (`REFERENCES`)
+        pub fn references(mut self, references: References<'a>) -> Self {
+            self.references = Some(references);
+            self
+        }
     }
 
-    #[test]
-    fn test_insert_query() {
-        let query = query_builder::insert("users")
-            .set("name", "$1")
-            .set("karma", "$2")
-            .build();
-        let possibility1 = "INSERT INTO users (name, karma) VALUES ($1, $2);" == query;
-        let possibility2 = "INSERT INTO users (karma, name) VALUES ($2, $1);" == query;
-        assert!(possibility1 || possibility2);
+    impl<'a> References<'a> {
+        /// Construct a reference to columns of a table (or its primary key, if `columns` is
+        /// empty)
+        pub fn new<T: Into<Ident<'a>>>(table: T, columns: &[&'a str]) -> Self {
+            References {
+                table: table.into(),
+                columns: columns.iter().map(|c| Ident::new(*c)).collect(),
+                on_delete: None,
+                on_update: None,
+            }
+        }
+
+        /// What happens when the referenced row is deleted (`ON DELETE`)
+        pub fn on_delete(mut self, action: ReferentialAction) -> Self {
+            self.on_delete = Some(action);
+            self
+        }
+
+        /// What happens when the referenced row is updated (`ON UPDATE`)
+        pub fn on_update(mut self, action: ReferentialAction) -> Self {
+            self.on_update = Some(action);
+            self
+        }
     }
 
-    #[test]
-    fn test_select_query() {
-        let query = query_builder::select("users").build();
-        assert_eq!("SELECT * FROM users;", query);
+    impl<'a> OrderBy<'a> {
+        /// Construct a sort key for the value of an expression
+        pub fn new<E: Into<Expr<'a>>>(expr: E, direction: Order) -> Self {
+            OrderBy {
+                key: SortKey::Expr(expr.into()),
+                direction,
+                nulls: None,
+                collation: None,
+            }
+        }
+
+        /// Construct a sort key for a column of the result set, counting from 1
+        pub fn position(position: usize, direction: Order) -> Self {
+            OrderBy {
+                key: SortKey::Position(position),
+                direction,
+                nulls: None,
+                collation: None,
+            }
+        }
+
+        /// Place `NULL` values first or last (`NULLS FIRST` or `NULLS LAST`)
+        ///
+        /// MySQL has no `NULLS` option, so it is emulated by sorting on `IS NULL` first.
+        pub fn nulls(mut self, nulls: Nulls) -> Self {
+            self.nulls = Some(nulls);
+            self
+        }
+
+        /// Compare values using a collation (`COLLATE`)
+        pub fn collate<S: Into<Cow<'a, str>>>(mut self, collation: S) -> Self {
+            self.collation = Some(collation.into());
+            self
+        }
     }
 
-    #[test]
-    fn test_select_query_with_fields() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name"])
-            .build();
-        assert_eq!("SELECT id, name FROM users;", query);
+    impl FrameBound {
+        /// Position of the bound relative to the current row, ignoring the number of rows
+        fn rank(self) -> u8 {
+            match self {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(_) => 1,
+                FrameBound::CurrentRow => 2,
+                FrameBound::Following(_) => 3,
+                FrameBound::UnboundedFollowing => 4,
+            }
+        }
+
+        fn write(&self, query: &mut Writer) {
+            match *self {
+                FrameBound::UnboundedPreceding => query.push("UNBOUNDED PRECEDING"),
+                FrameBound::Preceding(n) => {
+                    query.push(n.to_string().as_str());
+                    query.push(" PRECEDING");
+                }
+                FrameBound::CurrentRow => query.push("CURRENT ROW"),
+                FrameBound::Following(n) => {
+                    query.push(n.to_string().as_str());
+                    query.push(" FOLLOWING");
+                }
+                FrameBound::UnboundedFollowing => query.push("UNBOUNDED FOLLOWING"),
+            }
+        }
     }
 
-    #[test]
-    fn test_select_query_with_alias() {
-        let query = query_builder::select("users")
-            .alias("users", "u")
-            .fields(&["id", "name"])
-            .build();
-        assert_eq!("SELECT id, name FROM users AS u;", query);
+    impl SetOperation {
+        fn as_str(&self) -> &'static str {
+            match *self {
+                SetOperation::Union => " UNION ",
+                SetOperation::UnionAll => " UNION ALL ",
+                SetOperation::Intersect => " INTERSECT ",
+                SetOperation::Except => " EXCEPT ",
+            }
+        }
+    }
+
+    impl Dialect {
+        /// Quote an identifier, escaping embedded quote characters
+        pub fn quote(self, ident: &str) -> String {
+            let quote = match self {
+                Dialect::Postgres | Dialect::Sqlite => '"',
+                Dialect::MySql => '`',
+            };
+
+            let mut quoted = String::with_capacity(ident.len() + 2);
+            quoted.push(quote);
+            for c in ident.chars() {
+                if c == quote {
+                    quoted.push(quote);
+                }
+                quoted.push(c);
+            }
+            quoted.push(quote);
+            quoted
+        }
+
+        /// Whether `name` must be quoted to be used as an identifier: it is not a plain name, is
+        /// a reserved word, or has uppercase letters (which PostgreSQL folds to lowercase unless
+        /// the name is quoted)
+        fn needs_quotes(self, name: &str) -> bool {
+            let keywords = match self {
+                Dialect::Postgres => KEYWORDS_POSTGRES,
+                Dialect::MySql => KEYWORDS_MYSQL,
+                Dialect::Sqlite => KEYWORDS_SQLITE,
+            };
+
+            !is_name(name)
+                || KEYWORDS
+                    .iter()
+                    .chain(keywords)
+                    .any(|k| k.eq_ignore_ascii_case(name))
+                || (self == Dialect::Postgres && name.bytes().any(|b| b.is_ascii_uppercase()))
+        }
+
+        /// Placeholder for the `n`th (1-based) bound value
+        fn placeholder(self, n: usize) -> String {
+            match self {
+                Dialect::Postgres => format!("${}", n),
+                Dialect::MySql | Dialect::Sqlite => String::from("?"),
+            }
+        }
+
+        /// `value` written as a literal
+        fn literal(self, value: &Value) -> String {
+            match *value {
+                Value::Null => String::from("NULL"),
+                Value::Bool(v) => match (self, v) {
+                    (Dialect::Sqlite, true) => String::from("1"),
+                    (Dialect::Sqlite, false) => String::from("0"),
+                    (_, true) => String::from("TRUE"),
+                    (_, false) => String::from("FALSE"),
+                },
+                Value::Int(v) => v.to_string(),
+                Value::Float(v) if v.is_finite() => format!("{:?}", v),
+                Value::Float(v) => self.literal(&Value::Text(v.to_string())),
+                Value::Text(ref v) => {
+                    let mut quoted = String::with_capacity(v.len() + 2);
+                    quoted.push('\'');
+                    for c in v.chars() {
+                        match c {
+                            '\'' => quoted.push_str("''"),
+                            '\\' if self == Dialect::MySql => quoted.push_str("\\\\"),
+                            _ => quoted.push(c),
+                        }
+                    }
+                    quoted.push('\'');
+                    quoted
+                }
+                Value::Bytes(ref v) => {
+                    let hex: String = v.iter().map(|b| format!("{:02x}", b)).collect();
+                    match self {
+                        Dialect::Postgres => format!("'\\x{}'::bytea", hex),
+                        Dialect::MySql | Dialect::Sqlite => format!("X'{}'", hex),
+                    }
+                }
+            }
+        }
+
+        /// Whether `INSERT`, `UPDATE` and `DELETE` support a `RETURNING` clause
+        fn supports_returning(self) -> bool {
+            match self {
+                Dialect::Postgres | Dialect::Sqlite => true,
+                Dialect::MySql => false,
+            }
+        }
+
+        /// The `LIMIT` used when only an `OFFSET` is given, for dialects that require both
+        fn unbounded_limit(self) -> Option<&'static str> {
+            match self {
+                Dialect::Postgres => None,
+                Dialect::MySql => Some("18446744073709551615"),
+                Dialect::Sqlite => Some("-1"),
+            }
+        }
+    }
+
+    impl fmt::Display for BuildError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                BuildError::Unsupported { dialect, feature } => {
+                    write!(f, "{} does not support {}", dialect, feature)
+                }
+                BuildError::RowMismatch { row } => write!(
+                    f,
+                    "row {} does not supply the same columns as the other rows",
+                    row
+                ),
+                BuildError::MissingColumns => {
+                    write!(f, "INSERT ... SELECT requires a list of columns")
+                }
+                BuildError::ColumnCountMismatch { expected, found } => {
+                    write!(f, "expected {} columns, found {}", expected, found)
+                }
+                BuildError::Incompatible { clause, with } => {
+                    write!(f, "{} cannot be used with {}", clause, with)
+                }
+                BuildError::DistinctOnOrder => write!(
+                    f,
+                    "DISTINCT ON expressions must match the leftmost ORDER BY expressions"
+                ),
+                BuildError::EmptyColumns { clause } => {
+                    write!(f, "{} requires at least one column", clause)
+                }
+                BuildError::MissingClause { clause, requires } => {
+                    write!(f, "{} requires {}", clause, requires)
+                }
+                BuildError::BoundValueMismatch { markers, values } => write!(
+                    f,
+                    "expected {} bound values for the `?` markers, found {}",
+                    markers, values
+                ),
+                BuildError::InvalidFrame => write!(f, "window frame starts after it ends"),
+            }
+        }
+    }
+
+    impl Error for BuildError {}
+
+    impl<'a> Ident<'a> {
+        /// Construct an unqualified identifier; `name` is used as-is, even if it contains `.`
+        pub fn new<S: Into<Cow<'a, str>>>(name: S) -> Self {
+            Ident {
+                parts: vec![name.into()],
+            }
+        }
+
+        /// Construct an identifier from a `.`-separated path (e.g., `schema.table`)
+        pub fn path<S: Into<Cow<'a, str>>>(path: S) -> Self {
+            let parts = match path.into() {
+                Cow::Borrowed(path) => path.split('.').map(Cow::Borrowed).collect(),
+                Cow::Owned(path) => path.split('.').map(|p| Cow::Owned(p.to_string())).collect(),
+            };
+            Ident { parts }
+        }
+    }
+
+    impl<'a> From<&'a str> for Table<'a> {
+        fn from(path: &'a str) -> Self {
+            Table::Named(Ident::path(path))
+        }
+    }
+
+    impl<'a> From<String> for Table<'a> {
+        fn from(path: String) -> Self {
+            Table::Named(Ident::path(path))
+        }
+    }
+
+    impl<'a> From<Ident<'a>> for Table<'a> {
+        fn from(ident: Ident<'a>) -> Self {
+            Table::Named(ident)
+        }
+    }
+
+    impl<'a> From<&'a str> for Ident<'a> {
+        fn from(path: &'a str) -> Self {
+            Ident::path(path)
+        }
+    }
+
+    impl<'a> From<String> for Ident<'a> {
+        fn from(path: String) -> Self {
+            Ident::path(path)
+        }
+    }
+
+    impl<'a, C: TypedColumn> From<C> for Ident<'a> {
+        fn from(_: C) -> Self {
+            Ident::new(C::NAME)
+        }
+    }
+
+    impl<'a, V: Operand<'a>> Assign<'a, V> for &'a str {
+        fn assign(self, value: V) -> (Ident<'a>, Expr<'a>) {
+            (self.into(), value.into_expr())
+        }
+    }
+
+    impl<'a, V: Operand<'a>> Assign<'a, V> for String {
+        fn assign(self, value: V) -> (Ident<'a>, Expr<'a>) {
+            (self.into(), value.into_expr())
+        }
+    }
+
+    impl<'a, V: Operand<'a>> Assign<'a, V> for Ident<'a> {
+        fn assign(self, value: V) -> (Ident<'a>, Expr<'a>) {
+            (self, value.into_expr())
+        }
+    }
+
+    impl<'a, C: TypedColumn, V: Into<C::Type>> Assign<'a, V> for C {
+        fn assign(self, value: V) -> (Ident<'a>, Expr<'a>) {
+            (self.into(), typed_value::<C, V>(value))
+        }
+    }
+
+    impl<'a> fmt::Debug for Ident<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Debug::fmt(&self.parts.join("."), f)
+        }
+    }
+
+    macro_rules! impl_from_for_value {
+        ($($t:ty => $variant:ident as $cast:ty),*) => {
+            $(
+                impl From<$t> for Value {
+                    fn from(v: $t) -> Self {
+                        Value::$variant(v as $cast)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_from_for_value!(
+        i8 => Int as i64,
+        i16 => Int as i64,
+        i32 => Int as i64,
+        i64 => Int as i64,
+        u8 => Int as i64,
+        u16 => Int as i64,
+        u32 => Int as i64,
+        f32 => Float as f64,
+        f64 => Float as f64
+    );
+
+    impl From<bool> for Value {
+        fn from(v: bool) -> Self {
+            Value::Bool(v)
+        }
+    }
+
+    impl<'a> From<&'a str> for Value {
+        fn from(v: &'a str) -> Self {
+            Value::Text(v.to_string())
+        }
+    }
+
+    impl From<String> for Value {
+        fn from(v: String) -> Self {
+            Value::Text(v)
+        }
+    }
+
+    impl<'a> From<&'a [u8]> for Value {
+        fn from(v: &'a [u8]) -> Self {
+            Value::Bytes(v.to_vec())
+        }
+    }
+
+    impl From<Vec<u8>> for Value {
+        fn from(v: Vec<u8>) -> Self {
+            Value::Bytes(v)
+        }
+    }
+
+    impl<T: Into<Value>> From<Option<T>> for Value {
+        fn from(v: Option<T>) -> Self {
+            match v {
+                Some(v) => v.into(),
+                None => Value::Null,
+            }
+        }
+    }
+
+    impl Writer {
+        fn new(dialect: Dialect) -> Self {
+            Writer {
+                dialect,
+                sql: String::new(),
+                params: Vec::new(),
+                literals: false,
+            }
+        }
+
+        /// Append SQL text verbatim
+        fn push(&mut self, sql: &str) {
+            self.sql += sql;
+        }
+
+        /// Append an identifier, quoting each part as needed
+        fn push_ident(&mut self, ident: &Ident) {
+            for (i, part) in ident.parts.iter().enumerate() {
+                if i != 0 {
+                    self.push(".");
+                }
+                if *part == "*" || !self.dialect.needs_quotes(part) {
+                    self.push(part);
+                } else {
+                    self.sql += self.dialect.quote(part).as_str();
+                }
+            }
+        }
+
+        /// Append identifiers, with the `sep` `str` between each value
+        fn push_idents<'b, I: IntoIterator<Item = &'b Ident<'b>>>(&mut self, idents: I, sep: &str) {
+            for (i, ident) in idents.into_iter().enumerate() {
+                if i != 0 {
+                    self.push(sep);
+                }
+                self.push_ident(ident);
+            }
+        }
+
+        /// Append a placeholder (e.g., `$n` or `?`) and bind `value` to it, or `value` itself if
+        /// the query cannot take parameters
+        fn push_value(&mut self, value: &Value) {
+            if self.literals {
+                self.sql += self.dialect.literal(value).as_str();
+                return;
+            }
+
+            self.params.push(value.clone());
+            self.sql += self.dialect.placeholder(self.params.len()).as_str();
+        }
+
+        /// Append a `RETURNING` clause, if the dialect supports it
+        fn push_returning(&mut self, returns: &[Expr]) -> Result<(), BuildError> {
+            if !self.dialect.supports_returning() {
+                return Err(self.unsupported("RETURNING"));
+            }
+
+            self.push(" RETURNING ");
+            self.push_exprs(returns, ", ")
+        }
+
+        /// Error for a `feature` the dialect cannot express
+        fn unsupported(&self, feature: &'static str) -> BuildError {
+            BuildError::Unsupported {
+                dialect: self.dialect,
+                feature,
+            }
+        }
+
+        /// Append an expression
+        fn push_expr(&mut self, expr: &Expr) -> Result<(), BuildError> {
+            match *expr {
+                Expr::Raw(ref sql) => self.push(sql),
+                Expr::Bound(ref sql, ref values) => {
+                    let markers = markers(sql);
+                    if markers.len() != values.len() {
+                        return Err(BuildError::BoundValueMismatch {
+                            markers: markers.len(),
+                            values: values.len(),
+                        });
+                    }
+
+                    let mut start = 0;
+                    for (marker, value) in markers.into_iter().zip(values) {
+                        self.push(&sql[start..marker]);
+                        self.push_value(value);
+                        start = marker + 1;
+                    }
+                    self.push(&sql[start..]);
+                }
+                Expr::Column(ref ident) => self.push_ident(ident),
+                Expr::Value(ref value) => self.push_value(value),
+                Expr::And(ref exprs) => {
+                    self.push_junction(exprs, " AND ", "1 = 1", PRECEDENCE_AND)?
+                }
+                Expr::Or(ref exprs) => self.push_junction(exprs, " OR ", "1 = 0", PRECEDENCE_OR)?,
+                Expr::Not(ref expr) => {
+                    self.push("NOT ");
+                    self.push_operand(expr, PRECEDENCE_ATOM)?;
+                }
+                Expr::Compare(ref left, op, ref right) => {
+                    self.push_operand(left, PRECEDENCE_ATOM)?;
+                    self.push(op.as_str());
+                    self.push_operand(right, PRECEDENCE_ATOM)?;
+                }
+                Expr::In(ref expr, ref list) => {
+                    if list.is_empty() {
+                        self.push("1 = 0");
+                    } else {
+                        self.push_operand(expr, PRECEDENCE_ATOM)?;
+                        self.push(" IN (");
+                        self.push_exprs(list, ", ")?;
+                        self.push(")");
+                    }
+                }
+                Expr::InSubquery(ref expr, ref query) => {
+                    self.push_operand(expr, PRECEDENCE_ATOM)?;
+                    self.push(" IN (");
+                    query.write(self)?;
+                    self.push(")");
+                }
+                Expr::Between(ref expr, ref low, ref high) => {
+                    self.push_operand(expr, PRECEDENCE_ATOM)?;
+                    self.push(" BETWEEN ");
+                    self.push_operand(low, PRECEDENCE_ATOM)?;
+                    self.push(" AND ");
+                    self.push_operand(high, PRECEDENCE_ATOM)?;
+                }
+                Expr::IsNull(ref expr) => {
+                    self.push_operand(expr, PRECEDENCE_ATOM)?;
+                    self.push(" IS NULL");
+                }
+                Expr::IsNotNull(ref expr) => {
+                    self.push_operand(expr, PRECEDENCE_ATOM)?;
+                    self.push(" IS NOT NULL");
+                }
+                Expr::Exists(ref query) => {
+                    self.push("EXISTS (");
+                    query.write(self)?;
+                    self.push(")");
+                }
+                Expr::Subquery(ref query) => {
+                    self.push("(");
+                    query.write(self)?;
+                    self.push(")");
+                }
+                Expr::Excluded(ref ident) => match self.dialect {
+                    Dialect::Postgres | Dialect::Sqlite => {
+                        self.push("EXCLUDED.");
+                        self.push_ident(ident);
+                    }
+                    Dialect::MySql => {
+                        self.push("VALUES(");
+                        self.push_ident(ident);
+                        self.push(")");
+                    }
+                },
+                Expr::Over(ref expr, ref window) => {
+                    // The function call is written as-is, as `(f()) OVER` is not valid SQL
+                    self.push_expr(expr)?;
+                    self.push(" OVER ");
+                    match window.base {
+                        Some(ref name) if window.is_named() => self.push_ident(name),
+                        _ => {
+                            self.push("(");
+                            self.push_window(window)?;
+                            self.push(")");
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Append an expression, in parentheses if it binds less tightly than `precedence`
+        fn push_operand(&mut self, expr: &Expr, precedence: u8) -> Result<(), BuildError> {
+            if expr.precedence() < precedence {
+                self.push("(");
+                self.push_expr(expr)?;
+                self.push(")");
+                Ok(())
+            } else {
+                self.push_expr(expr)
+            }
+        }
+
+        /// Append expressions, with the `sep` `str` between each value
+        fn push_exprs(&mut self, exprs: &[Expr], sep: &str) -> Result<(), BuildError> {
+            for (i, expr) in exprs.iter().enumerate() {
+                if i != 0 {
+                    self.push(sep);
+                }
+                self.push_expr(expr)?;
+            }
+            Ok(())
+        }
+
+        /// Append expressions joined by `AND` or `OR`, or `empty` if there are none
+        fn push_junction(
+            &mut self,
+            exprs: &[Expr],
+            sep: &str,
+            empty: &str,
+            precedence: u8,
+        ) -> Result<(), BuildError> {
+            match exprs.len() {
+                0 => {
+                    self.push(empty);
+                    return Ok(());
+                }
+                1 => return self.push_expr(&exprs[0]),
+                _ => {}
+            }
+
+            for (i, expr) in exprs.iter().enumerate() {
+                if i != 0 {
+                    self.push(sep);
+                }
+                self.push_operand(expr, precedence)?;
+            }
+            Ok(())
+        }
+
+        /// Append `field = value` assignments of a `SET` clause
+        fn push_assignments(&mut self, values: &Assignments) -> Result<(), BuildError> {
+            for (i, (field, value)) in values.0.iter().enumerate() {
+                if i != 0 {
+                    self.push(", ");
+                }
+                self.push_ident(field);
+                self.push(" = ");
+                self.push_expr(value)?;
+            }
+            Ok(())
+        }
+
+        /// Append an `ON CONFLICT` (or `ON DUPLICATE KEY UPDATE`) clause for an `INSERT` of
+        /// `columns`
+        fn push_conflict(
+            &mut self,
+            conflict: &OnConflict,
+            columns: &[&Ident],
+        ) -> Result<(), BuildError> {
+            if self.dialect == Dialect::MySql {
+                // MySQL checks every unique index, so the target is not written
+                if conflict.conditions.is_some() {
+                    return Err(self.unsupported("ON DUPLICATE KEY UPDATE with WHERE"));
+                }
+
+                self.push(" ON DUPLICATE KEY UPDATE ");
+                if conflict.values.0.is_empty() {
+                    // Emulate `DO NOTHING` by assigning a column to itself
+                    match columns.first() {
+                        Some(column) => {
+                            self.push_ident(column);
+                            self.push(" = ");
+                            self.push_ident(column);
+                        }
+                        None => {
+                            return Err(self.unsupported("ON CONFLICT DO NOTHING without columns"))
+                        }
+                    }
+                } else {
+                    self.push_assignments(&conflict.values)?;
+                }
+                return Ok(());
+            }
+
+            self.push(" ON CONFLICT");
+            match conflict.target {
+                ConflictTarget::Any => {
+                    if self.dialect == Dialect::Postgres && !conflict.values.0.is_empty() {
+                        return Err(self.unsupported("ON CONFLICT DO UPDATE without a target"));
+                    }
+                }
+                ConflictTarget::Columns(ref columns) => {
+                    self.push(" (");
+                    self.push_idents(columns, ", ");
+                    self.push(")");
+                }
+                ConflictTarget::Constraint(ref name) => {
+                    if self.dialect == Dialect::Sqlite {
+                        return Err(self.unsupported("ON CONFLICT ON CONSTRAINT"));
+                    }
+                    self.push(" ON CONSTRAINT ");
+                    self.push_ident(name);
+                }
+            }
+
+            if conflict.values.0.is_empty() {
+                self.push(" DO NOTHING");
+            } else {
+                self.push(" DO UPDATE SET ");
+                self.push_assignments(&conflict.values)?;
+
+                if let Some(ref conditions) = conflict.conditions {
+                    self.push(" WHERE ");
+                    self.push_conditions(conditions)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Append a `WITH` clause, followed by a space
+        fn push_ctes(&mut self, ctes: &[Cte]) -> Result<(), BuildError> {
+            self.push("WITH ");
+            if ctes.iter().any(|cte| cte.recursive) {
+                self.push("RECURSIVE ");
+            }
+
+            for (i, cte) in ctes.iter().enumerate() {
+                if i != 0 {
+                    self.push(", ");
+                }
+                self.push_ident(&cte.name);
+                if let Some(ref columns) = cte.columns {
+                    if columns.is_empty() {
+                        return Err(BuildError::EmptyColumns { clause: "WITH" });
+                    }
+                    self.push(" (");
+                    self.push_idents(columns, ", ");
+                    self.push(")");
+                }
+
+                match cte.query {
+                    Statement::Select(_) | Statement::Compound(_) | Statement::Raw(_) => {}
+                    Statement::CreateTable(_)
+                    | Statement::CreateIndex(_)
+                    | Statement::AlterTable(_)
+                    | Statement::Drop(_) => {
+                        return Err(BuildError::Incompatible {
+                            clause: "WITH",
+                            with: "a schema statement",
+                        });
+                    }
+                    _ if self.dialect != Dialect::Postgres => {
+                        return Err(self.unsupported("data-modifying statements in WITH"));
+                    }
+                    _ => {}
+                }
+
+                self.push(" AS (");
+                cte.query.write(self)?;
+                self.push(")");
+            }
+
+            self.push(" ");
+            Ok(())
+        }
+
+        /// Append the definition of a window, without parentheses
+        fn push_window(&mut self, window: &Window) -> Result<(), BuildError> {
+            let mut sep = "";
+            if let Some(ref base) = window.base {
+                self.push_ident(base);
+                sep = " ";
+            }
+
+            if let Some(ref partitions) = window.partitions {
+                self.push(sep);
+                self.push("PARTITION BY ");
+                self.push_exprs(partitions, ", ")?;
+                sep = " ";
+            }
+
+            if let Some(ref order) = window.order {
+                self.push(sep);
+                self.push("ORDER BY ");
+                self.push_order(order)?;
+                sep = " ";
+            }
+
+            if let Some((ref units, start, end)) = window.frame {
+                if start == FrameBound::UnboundedFollowing
+                    || end == FrameBound::UnboundedPreceding
+                    || start.rank() > end.rank()
+                {
+                    return Err(BuildError::InvalidFrame);
+                }
+
+                self.push(sep);
+                match *units {
+                    FrameUnits::Rows => self.push("ROWS BETWEEN "),
+                    FrameUnits::Range => self.push("RANGE BETWEEN "),
+                }
+                start.write(self);
+                self.push(" AND ");
+                end.write(self);
+            }
+
+            Ok(())
+        }
+
+        /// Append the sort keys of an `ORDER BY` clause
+        fn push_order(&mut self, order: &[OrderBy]) -> Result<(), BuildError> {
+            for (i, key) in order.iter().enumerate() {
+                if i != 0 {
+                    self.push(", ");
+                }
+
+                // MySQL sorts `NULL` first in ascending order, and has no `NULLS` option; the key
+                // is written (and its values are bound) twice
+                if let (Dialect::MySql, Some(ref nulls)) = (self.dialect, &key.nulls) {
+                    match key.key {
+                        SortKey::Expr(ref expr) => self.push_operand(expr, PRECEDENCE_ATOM)?,
+                        SortKey::Position(_) => {
+                            return Err(self.unsupported("NULLS FIRST or LAST by column position"))
+                        }
+                    }
+                    match *nulls {
+                        Nulls::First => self.push(" IS NOT NULL, "),
+                        Nulls::Last => self.push(" IS NULL, "),
+                    }
+                }
+
+                match key.key {
+                    SortKey::Expr(ref expr) if key.collation.is_some() => {
+                        self.push_operand(expr, PRECEDENCE_ATOM)?
+                    }
+                    SortKey::Expr(ref expr) => self.push_expr(expr)?,
+                    SortKey::Position(position) => self.push(position.to_string().as_str()),
+                }
+
+                if let Some(ref collation) = key.collation {
+                    self.push(" COLLATE ");
+                    // PostgreSQL collation names are case-sensitive (e.g., "C" or "en_US")
+                    if self.dialect == Dialect::Postgres {
+                        self.sql += self.dialect.quote(collation).as_str();
+                    } else {
+                        self.push_ident(&Ident::new(collation.as_ref()));
+                    }
+                }
+
+                match key.direction {
+                    Order::Asc => self.push(" ASC"),
+                    Order::Desc => self.push(" DESC"),
+                }
+
+                match key.nulls {
+                    Some(_) if self.dialect == Dialect::MySql => {}
+                    Some(Nulls::First) => self.push(" NULLS FIRST"),
+                    Some(Nulls::Last) => self.push(" NULLS LAST"),
+                    None => {}
+                }
+            }
+            Ok(())
+        }
+
+        /// Append the keys of an index, with expressions other than columns in parentheses
+        fn push_index_keys(&mut self, keys: &[OrderBy]) -> Result<(), BuildError> {
+            for (i, key) in keys.iter().enumerate() {
+                if i != 0 {
+                    self.push(", ");
+                }
+
+                match key.key {
+                    SortKey::Expr(ref expr @ Expr::Column(_)) => self.push_expr(expr)?,
+                    SortKey::Expr(ref expr) => {
+                        self.push("(");
+                        self.push_expr(expr)?;
+                        self.push(")");
+                    }
+                    SortKey::Position(_) => {
+                        return Err(BuildError::Incompatible {
+                            clause: "CREATE INDEX",
+                            with: "a column position",
+                        });
+                    }
+                }
+
+                if let Some(ref collation) = key.collation {
+                    self.push(" COLLATE ");
+                    if self.dialect == Dialect::Postgres {
+                        self.sql += self.dialect.quote(collation).as_str();
+                    } else {
+                        self.push_ident(&Ident::new(collation.as_ref()));
+                    }
+                }
+
+                match key.direction {
+                    Order::Asc => self.push(" ASC"),
+                    Order::Desc => self.push(" DESC"),
+                }
+
+                if let Some(nulls) = key.nulls {
+                    if self.dialect != Dialect::Postgres {
+                        return Err(self.unsupported("NULLS FIRST or LAST in an index"));
+                    }
+                    match nulls {
+                        Nulls::First => self.push(" NULLS FIRST"),
+                        Nulls::Last => self.push(" NULLS LAST"),
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Append a `FOR UPDATE` or `FOR SHARE` clause
+        fn push_lock(&mut self, lock: &Lock) -> Result<(), BuildError> {
+            let strength = match lock.strength {
+                LockStrength::Update => "FOR UPDATE",
+                LockStrength::NoKeyUpdate => "FOR NO KEY UPDATE",
+                LockStrength::Share => "FOR SHARE",
+                LockStrength::KeyShare => "FOR KEY SHARE",
+            };
+
+            match (self.dialect, &lock.strength) {
+                (Dialect::Sqlite, _) => return Err(self.unsupported("FOR UPDATE or FOR SHARE")),
+                (Dialect::MySql, &LockStrength::NoKeyUpdate)
+                | (Dialect::MySql, &LockStrength::KeyShare) => {
+                    return Err(self.unsupported(strength))
+                }
+                _ => {}
+            }
+
+            self.push(" ");
+            self.push(strength);
+
+            if let Some(ref tables) = lock.tables {
+                if tables.is_empty() {
+                    return Err(BuildError::EmptyColumns { clause: "OF" });
+                }
+                self.push(" OF ");
+                self.push_idents(tables, ", ");
+            }
+
+            match lock.wait {
+                LockWait::Wait => {}
+                LockWait::NoWait => self.push(" NOWAIT"),
+                LockWait::SkipLocked => self.push(" SKIP LOCKED"),
+            }
+
+            Ok(())
+        }
+
+        /// Append `LIMIT` and `OFFSET` clauses, omitting those that are 0
+        fn push_limit(&mut self, limit: usize, offset: usize) {
+            if limit != 0 {
+                self.push(" LIMIT ");
+                self.push(limit.to_string().as_str());
+            } else if offset != 0 {
+                if let Some(limit) = self.dialect.unbounded_limit() {
+                    self.push(" LIMIT ");
+                    self.push(limit);
+                }
+            }
+
+            if offset != 0 {
+                self.push(" OFFSET ");
+                self.push(offset.to_string().as_str());
+            }
+        }
+
+        /// Append conditions of a `WHERE` or `HAVING` clause, which must all hold
+        fn push_conditions(&mut self, conditions: &[Expr]) -> Result<(), BuildError> {
+            self.push_junction(conditions, " AND ", "1 = 1", PRECEDENCE_AND)
+        }
+
+        /// Append an alteration of an `ALTER TABLE` query
+        fn push_alteration(&mut self, alteration: &Alteration) -> Result<(), BuildError> {
+            let dialect = self.dialect;
+            match *alteration {
+                Alteration::AddColumn(ref column) => {
+                    // SQLite cannot add a column that needs an index to an existing table
+                    if dialect == Dialect::Sqlite {
+                        if column.primary_key || column.auto_increment {
+                            return Err(self.unsupported("adding a PRIMARY KEY column"));
+                        }
+                        if column.unique {
+                            return Err(self.unsupported("adding a UNIQUE column"));
+                        }
+                        // Existing rows would need a value for it
+                        let default_null =
+                            matches!(column.default, None | Some(Expr::Value(Value::Null)));
+                        if column.not_null && default_null {
+                            return Err(
+                                self.unsupported("adding a NOT NULL column without a DEFAULT")
+                            );
+                        }
+                    }
+
+                    self.push("ADD COLUMN ");
+                    self.push_column(column)?;
+                    if let Some(ref references) = column.references {
+                        if dialect == Dialect::MySql {
+                            self.push(", ADD FOREIGN KEY (");
+                            self.push_ident(&column.name);
+                            self.push(") ");
+                            self.push_references(references, 1)?;
+                        }
+                    }
+                }
+                Alteration::DropColumn(ref column) => {
+                    self.push("DROP COLUMN ");
+                    self.push_ident(column);
+                }
+                Alteration::RenameColumn(ref column, ref name) => {
+                    self.push("RENAME COLUMN ");
+                    self.push_ident(column);
+                    self.push(" TO ");
+                    self.push_ident(name);
+                }
+                Alteration::AlterType(ref column, ref data_type) => {
+                    // MySQL can only change a column's type by redefining all of it
+                    if dialect != Dialect::Postgres {
+                        return Err(self.unsupported("changing a column's type"));
+                    }
+                    self.push("ALTER COLUMN ");
+                    self.push_ident(column);
+                    self.push(" TYPE ");
+                    self.push_data_type(data_type);
+                }
+                Alteration::SetDefault(ref column, ref default) => {
+                    if dialect == Dialect::Sqlite {
+                        return Err(self.unsupported("changing a column's default"));
+                    }
+                    self.push("ALTER COLUMN ");
+                    self.push_ident(column);
+                    self.push(" SET DEFAULT ");
+                    self.push_expr(default)?;
+                }
+                Alteration::DropDefault(ref column) => {
+                    if dialect == Dialect::Sqlite {
+                        return Err(self.unsupported("changing a column's default"));
+                    }
+                    self.push("ALTER COLUMN ");
+                    self.push_ident(column);
+                    self.push(" DROP DEFAULT");
+                }
+                Alteration::SetNotNull(ref column) | Alteration::DropNotNull(ref column) => {
+                    if dialect != Dialect::Postgres {
+                        return Err(self.unsupported("changing a column's nullability"));
+                    }
+                    self.push("ALTER COLUMN ");
+                    self.push_ident(column);
+                    match *alteration {
+                        Alteration::SetNotNull(_) => self.push(" SET NOT NULL"),
+                        _ => self.push(" DROP NOT NULL"),
+                    }
+                }
+                Alteration::AddConstraint(ref name, ref constraint) => {
+                    if dialect == Dialect::Sqlite {
+                        return Err(self.unsupported("adding constraints to a table"));
+                    }
+                    self.push("ADD CONSTRAINT ");
+                    self.push_ident(name);
+                    self.push(" ");
+                    self.push_table_constraint(constraint)?;
+                }
+                Alteration::DropConstraint(ref name) => {
+                    if dialect == Dialect::Sqlite {
+                        return Err(self.unsupported("dropping constraints from a table"));
+                    }
+                    self.push("DROP CONSTRAINT ");
+                    self.push_ident(name);
+                }
+                Alteration::RenameTo(ref table) => {
+                    self.push("RENAME TO ");
+                    self.push_ident(table);
+                }
+            }
+
+            Ok(())
+        }
+
+        fn push_column(&mut self, column: &Column) -> Result<(), BuildError> {
+            self.push_ident(&column.name);
+            self.push(" ");
+
+            if column.auto_increment {
+                match column.data_type {
+                    DataType::SmallInt | DataType::Integer | DataType::BigInt => {}
+                    _ => {
+                        return Err(BuildError::Incompatible {
+                            clause: "an auto-increment column",
+                            with: "a non-integer type",
+                        });
+                    }
+                }
+                if column.default.is_some() {
+                    return Err(BuildError::Incompatible {
+                        clause: "an auto-increment column",
+                        with: "DEFAULT",
+                    });
+                }
+                // SQLite only assigns values automatically to an `INTEGER PRIMARY KEY`
+                if self.dialect == Dialect::Sqlite {
+                    if !column.primary_key {
+                        return Err(self.unsupported("auto-increment columns without PRIMARY KEY"));
+                    }
+                    self.push("INTEGER");
+                } else {
+                    self.push_data_type(&column.data_type);
+                }
+            } else {
+                self.push_data_type(&column.data_type);
+            }
+
+            if column.not_null {
+                self.push(" NOT NULL");
+            }
+
+            if let Some(ref default) = column.default {
+                self.push(" DEFAULT ");
+                self.push_expr(default)?;
+            }
+
+            if column.auto_increment {
+                match self.dialect {
+                    Dialect::Postgres => self.push(" GENERATED BY DEFAULT AS IDENTITY"),
+                    Dialect::MySql => self.push(" AUTO_INCREMENT"),
+                    Dialect::Sqlite => {}
+                }
+            }
+
+            if column.primary_key {
+                self.push(" PRIMARY KEY");
+            }
+
+            if column.unique {
+                self.push(" UNIQUE");
+            }
+
+            for check in &column.checks {
+                self.push(" CHECK (");
+                self.push_expr(check)?;
+                self.push(")");
+            }
+
+            // MySQL parses but ignores `REFERENCES` in a column definition, so the table's
+            // `FOREIGN KEY` constraints are used instead
+            if let Some(ref references) = column.references {
+                if self.dialect != Dialect::MySql {
+                    self.push(" ");
+                    self.push_references(references, 1)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        fn push_data_type(&mut self, data_type: &DataType) {
+            let dialect = self.dialect;
+            match *data_type {
+                DataType::SmallInt => self.push("SMALLINT"),
+                DataType::Integer => self.push("INTEGER"),
+                DataType::BigInt => self.push("BIGINT"),
+                DataType::Real => self.push(match dialect {
+                    Dialect::MySql => "FLOAT",
+                    Dialect::Postgres | Dialect::Sqlite => "REAL",
+                }),
+                DataType::Double => self.push(match dialect {
+                    Dialect::Postgres => "DOUBLE PRECISION",
+                    Dialect::MySql => "DOUBLE",
+                    Dialect::Sqlite => "REAL",
+                }),
+                DataType::Decimal(precision, scale) => {
+                    let name = match dialect {
+                        Dialect::MySql => "DECIMAL",
+                        Dialect::Postgres | Dialect::Sqlite => "NUMERIC",
+                    };
+                    self.sql += format!("{}({}, {})", name, precision, scale).as_str();
+                }
+                DataType::Boolean => self.push("BOOLEAN"),
+                DataType::Char(length) => self.sql += format!("CHAR({})", length).as_str(),
+                DataType::VarChar(length) => self.sql += format!("VARCHAR({})", length).as_str(),
+                DataType::Text => self.push("TEXT"),
+                DataType::Bytes => self.push(match dialect {
+                    Dialect::Postgres => "BYTEA",
+                    Dialect::MySql | Dialect::Sqlite => "BLOB",
+                }),
+                DataType::Date => self.push("DATE"),
+                DataType::Time => self.push("TIME"),
+                DataType::Timestamp => self.push(match dialect {
+                    Dialect::MySql => "DATETIME",
+                    Dialect::Postgres | Dialect::Sqlite => "TIMESTAMP",
+                }),
+                DataType::Json => self.push(match dialect {
+                    Dialect::Postgres => "JSONB",
+                    Dialect::MySql => "JSON",
+                    Dialect::Sqlite => "TEXT",
+                }),
+                DataType::Uuid => self.push(match dialect {
+                    Dialect::Postgres => "UUID",
+                    Dialect::MySql => "CHAR(36)",
+                    Dialect::Sqlite => "TEXT",
+                }),
+                DataType::Custom(ref name) => self.push(name),
+            }
+        }
+
+        /// Append a `REFERENCES` clause, for a foreign key of `columns` columns
+        fn push_references(
+            &mut self,
+            references: &References,
+            columns: usize,
+        ) -> Result<(), BuildError> {
+            self.push("REFERENCES ");
+            self.push_ident(&references.table);
+
+            if references.columns.is_empty() {
+                // Without columns, the referenced table's primary key is used
+                if self.dialect == Dialect::MySql {
+                    return Err(self.unsupported("REFERENCES without columns"));
+                }
+            } else {
+                if references.columns.len() != columns {
+                    return Err(BuildError::ColumnCountMismatch {
+                        expected: columns,
+                        found: references.columns.len(),
+                    });
+                }
+                self.push(" (");
+                self.push_idents(&references.columns, ", ");
+                self.push(")");
+            }
+
+            if let Some(action) = references.on_delete {
+                self.push(" ON DELETE ");
+                self.push_referential_action(action)?;
+            }
+
+            if let Some(action) = references.on_update {
+                self.push(" ON UPDATE ");
+                self.push_referential_action(action)?;
+            }
+
+            Ok(())
+        }
+
+        fn push_referential_action(&mut self, action: ReferentialAction) -> Result<(), BuildError> {
+            self.push(match action {
+                ReferentialAction::NoAction => "NO ACTION",
+                ReferentialAction::Restrict => "RESTRICT",
+                ReferentialAction::Cascade => "CASCADE",
+                ReferentialAction::SetNull => "SET NULL",
+                ReferentialAction::SetDefault if self.dialect == Dialect::MySql => {
+                    return Err(self.unsupported("SET DEFAULT"));
+                }
+                ReferentialAction::SetDefault => "SET DEFAULT",
+            });
+            Ok(())
+        }
+
+        fn push_table_constraint(
+            &mut self,
+            constraint: &TableConstraint,
+        ) -> Result<(), BuildError> {
+            let (clause, columns) = match *constraint {
+                TableConstraint::PrimaryKey(ref columns) => ("PRIMARY KEY", columns),
+                TableConstraint::Unique(ref columns) => ("UNIQUE", columns),
+                TableConstraint::ForeignKey(ref columns, _) => ("FOREIGN KEY", columns),
+                TableConstraint::Check(ref check) => {
+                    self.push("CHECK (");
+                    self.push_expr(check)?;
+                    self.push(")");
+                    return Ok(());
+                }
+            };
+
+            if columns.is_empty() {
+                return Err(BuildError::EmptyColumns { clause });
+            }
+
+            self.push(clause);
+            self.push(" (");
+            self.push_idents(columns, ", ");
+            self.push(")");
+
+            if let TableConstraint::ForeignKey(_, ref references) = *constraint {
+                self.push(" ");
+                self.push_references(references, columns.len())?;
+            }
+
+            Ok(())
+        }
+
+        /// Terminate the query and return its SQL and bound values
+        fn finish(mut self) -> Result<(String, Vec<Value>), BuildError> {
+            self.sql += ";";
+            Ok((self.sql, self.params))
+        }
+    }
+
+    /// Whether `exprs` are the leftmost sort keys of `order`, in any order
+    fn leads_order(
+        exprs: &[Expr],
+        order: &[OrderBy],
+        dialect: Dialect,
+    ) -> Result<bool, BuildError> {
+        if order.len() < exprs.len() {
+            return Ok(false);
+        }
+
+        let mut keys = Vec::with_capacity(exprs.len());
+        for key in order[..exprs.len()].iter() {
+            match key.key {
+                SortKey::Expr(ref expr) => keys.push(expr.to_sql(dialect)?),
+                SortKey::Position(_) => return Ok(false),
+            }
+        }
+
+        for expr in exprs {
+            let expr = expr.to_sql(dialect)?;
+            match keys.iter().position(|key| *key == expr) {
+                Some(i) => {
+                    keys.remove(i);
+                }
+                None => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Write a query's SQL, or a description of why it is invalid (so formatting never panics)
+    fn write_query(
+        f: &mut fmt::Formatter,
+        query: Result<(String, Vec<Value>), BuildError>,
+    ) -> fmt::Result {
+        match query {
+            Ok((sql, _)) => f.write_str(&sql),
+            Err(err) => write!(f, "<invalid query: {}>", err),
+        }
+    }
+
+    impl<'a> fmt::Display for AlterTable<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Compound<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for CreateIndex<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for CreateTable<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Delete<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for DropObject<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Insert<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Select<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Update<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> fmt::Display for Statement<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write_query(f, self.try_build())
+        }
+    }
+
+    impl<'a> Statement<'a> {
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Generate SQL query (`String`) for `dialect`, with values written into it as literals
+        /// (e.g., for a script)
+        pub(crate) fn build_literal(&self, dialect: Dialect) -> Result<String, BuildError> {
+            let mut query = Writer::new(dialect);
+            query.literals = true;
+            self.write(&mut query)?;
+            query.finish().map(|(sql, _)| sql)
+        }
+
+        /// Whether the statement can run inside a transaction (`CREATE INDEX CONCURRENTLY`
+        /// cannot)
+        pub(crate) fn is_transactional(&self) -> bool {
+            match *self {
+                Statement::CreateIndex(ref create) => !create.concurrently,
+                _ => true,
+            }
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            match *self {
+                Statement::Select(ref select) => select.write(query),
+                Statement::Compound(ref compound) => compound.write(query),
+                Statement::Insert(ref insert) => insert.write(query),
+                Statement::Update(ref update) => update.write(query),
+                Statement::Delete(ref delete) => delete.write(query),
+                Statement::CreateTable(ref create) => create.write(query),
+                Statement::CreateIndex(ref create) => create.write(query),
+                Statement::AlterTable(ref alter) => alter.write(query),
+                Statement::Drop(ref drop) => drop.write(query),
+                Statement::Raw(ref sql) => {
+                    query.push(sql);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    impl<'a> From<Select<'a>> for Statement<'a> {
+        fn from(query: Select<'a>) -> Self {
+            Statement::Select(Box::new(query))
+        }
+    }
+
+    impl<'a> From<Compound<'a>> for Statement<'a> {
+        fn from(query: Compound<'a>) -> Self {
+            Statement::Compound(Box::new(query))
+        }
+    }
+
+    impl<'a> From<Insert<'a>> for Statement<'a> {
+        fn from(query: Insert<'a>) -> Self {
+            Statement::Insert(Box::new(query))
+        }
+    }
+
+    impl<'a> From<Update<'a>> for Statement<'a> {
+        fn from(query: Update<'a>) -> Self {
+            Statement::Update(Box::new(query))
+        }
+    }
+
+    impl<'a> From<AlterTable<'a>> for Statement<'a> {
+        fn from(query: AlterTable<'a>) -> Self {
+            Statement::AlterTable(Box::new(query))
+        }
+    }
+
+    impl<'a> From<DropObject<'a>> for Statement<'a> {
+        fn from(query: DropObject<'a>) -> Self {
+            Statement::Drop(Box::new(query))
+        }
+    }
+
+    impl<'a> From<CreateIndex<'a>> for Statement<'a> {
+        fn from(query: CreateIndex<'a>) -> Self {
+            Statement::CreateIndex(Box::new(query))
+        }
+    }
+
+    impl<'a> From<CreateTable<'a>> for Statement<'a> {
+        fn from(query: CreateTable<'a>) -> Self {
+            Statement::CreateTable(Box::new(query))
+        }
+    }
+
+    impl<'a> From<Delete<'a>> for Statement<'a> {
+        fn from(query: Delete<'a>) -> Self {
+            Statement::Delete(Box::new(query))
+        }
+    }
+
+    impl<'a> From<&'a str> for Statement<'a> {
+        fn from(sql: &'a str) -> Self {
+            Statement::Raw(Cow::Borrowed(sql))
+        }
+    }
+
+    impl<'a> From<String> for Statement<'a> {
+        fn from(sql: String) -> Self {
+            Statement::Raw(Cow::Owned(sql))
+        }
+    }
+
+    impl<'a> AlterTable<'a> {
+        /// Construct a new `ALTER TABLE` query builder
+        pub fn new<T: Into<Ident<'a>>>(table: T) -> Self {
+            AlterTable {
+                table: table.into(),
+                alterations: Vec::new(),
+            }
+        }
+
+        /// Add a column (`ADD COLUMN`)
+        pub fn add_column(&mut self, column: Column<'a>) -> &mut Self {
+            self.alterations.push(Alteration::AddColumn(column));
+            self
+        }
+
+        /// Remove a column (`DROP COLUMN`)
+        pub fn drop_column<C: Into<Ident<'a>>>(&mut self, column: C) -> &mut Self {
+            self.alterations.push(Alteration::DropColumn(column.into()));
+            self
+        }
+
+        /// Rename a column (`RENAME COLUMN`)
+        pub fn rename_column<C: Into<Ident<'a>>, N: Into<Ident<'a>>>(
+            &mut self,
+            column: C,
+            name: N,
+        ) -> &mut Self {
+            let alteration = Alteration::RenameColumn(column.into(), name.into());
+            self.alterations.push(alteration);
+            self
+        }
+
+        /// Change the type of a column (`ALTER COLUMN ... TYPE`)
+        pub fn alter_type<C: Into<Ident<'a>>>(
+            &mut self,
+            column: C,
+            data_type: DataType<'a>,
+        ) -> &mut Self {
+            let alteration = Alteration::AlterType(column.into(), data_type);
+            self.alterations.push(alteration);
+            self
+        }
+
+        /// Change the value (or expression, used as-is) a column uses when a row does not
+        /// supply one (`ALTER COLUMN ... SET DEFAULT`)
+        pub fn set_default<C: Into<Ident<'a>>, V: Operand<'a>>(
+            &mut self,
+            column: C,
+            value: V,
+        ) -> &mut Self {
+            let alteration = Alteration::SetDefault(column.into(), value.into_expr());
+            self.alterations.push(alteration);
+            self
+        }
+
+        /// Remove the default value of a column (`ALTER COLUMN ... DROP DEFAULT`)
+        pub fn drop_default<C: Into<Ident<'a>>>(&mut self, column: C) -> &mut Self {
+            self.alterations
+                .push(Alteration::DropDefault(column.into()));
+            self
+        }
+
+        /// Reject `NULL` values in a column (`ALTER COLUMN ... SET NOT NULL`)
+        pub fn set_not_null<C: Into<Ident<'a>>>(&mut self, column: C) -> &mut Self {
+            self.alterations.push(Alteration::SetNotNull(column.into()));
+            self
+        }
+
+        /// Allow `NULL` values in a column (`ALTER COLUMN ... DROP NOT NULL`)
+        pub fn drop_not_null<C: Into<Ident<'a>>>(&mut self, column: C) -> &mut Self {
+            self.alterations
+                .push(Alteration::DropNotNull(column.into()));
+            self
+        }
+
+        /// Add a named `PRIMARY KEY` constraint (`ADD CONSTRAINT`)
+        pub fn add_primary_key<N>(&mut self, name: N, columns: &[&'a str]) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+        {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.add_constraint(name, TableConstraint::PrimaryKey(columns))
+        }
+
+        /// Add a named `UNIQUE` constraint (`ADD CONSTRAINT`)
+        pub fn add_unique<N>(&mut self, name: N, columns: &[&'a str]) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+        {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.add_constraint(name, TableConstraint::Unique(columns))
+        }
+
+        /// Add a named `CHECK` constraint (`ADD CONSTRAINT`)
+        pub fn add_check<N, E>(&mut self, name: N, expr: E) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            E: Into<Expr<'a>>,
+        {
+            self.add_constraint(name, TableConstraint::Check(expr.into()))
+        }
+
+        /// Add a named `FOREIGN KEY` constraint (`ADD CONSTRAINT`)
+        pub fn add_foreign_key<N>(
+            &mut self,
+            name: N,
+            columns: &[&'a str],
+            references: References<'a>,
+        ) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+        {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.add_constraint(name, TableConstraint::ForeignKey(columns, references))
+        }
+
+        fn add_constraint<N: Into<Cow<'a, str>>>(
+            &mut self,
+            name: N,
+            constraint: TableConstraint<'a>,
+        ) -> &mut Self {
+            let alteration = Alteration::AddConstraint(Ident::new(name), constraint);
+            self.alterations.push(alteration);
+            self
+        }
+
+        /// Remove a named constraint (`DROP CONSTRAINT`)
+        pub fn drop_constraint<N: Into<Cow<'a, str>>>(&mut self, name: N) -> &mut Self {
+            let alteration = Alteration::DropConstraint(Ident::new(name));
+            self.alterations.push(alteration);
+            self
+        }
+
+        /// Rename the table (`RENAME TO`)
+        pub fn rename_to<T: Into<Ident<'a>>>(&mut self, table: T) -> &mut Self {
+            self.alterations.push(Alteration::RenameTo(table.into()));
+            self
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls
+        ///
+        /// Values are written into the query as literals, so none are returned.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid; use `try_build` to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls, or
+        /// an error if the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            if self.alterations.is_empty() {
+                return Err(BuildError::MissingClause {
+                    clause: "ALTER TABLE",
+                    requires: "an alteration",
+                });
+            }
+
+            if self.alterations.len() > 1 {
+                match query.dialect {
+                    // SQLite makes one change per `ALTER TABLE`
+                    Dialect::Sqlite => {
+                        return Err(query.unsupported("multiple alterations in one ALTER TABLE"));
+                    }
+                    // PostgreSQL cannot combine renames with other alterations
+                    Dialect::Postgres => {
+                        let renames = self.alterations.iter().any(|a| {
+                            matches!(*a, Alteration::RenameColumn(..) | Alteration::RenameTo(_))
+                        });
+                        if renames {
+                            return Err(BuildError::Incompatible {
+                                clause: "RENAME",
+                                with: "other alterations",
+                            });
+                        }
+                    }
+                    Dialect::MySql => {}
+                }
+            }
+
+            query.literals = true;
+
+            query.push("ALTER TABLE ");
+            query.push_ident(&self.table);
+            query.push(" ");
+
+            for (i, alteration) in self.alterations.iter().enumerate() {
+                if i != 0 {
+                    query.push(", ");
+                }
+                query.push_alteration(alteration)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> Compound<'a> {
+        /// Construct a new compound query builder, starting with the rows of `first`
+        pub fn new(first: Select<'a>) -> Self {
+            Compound {
+                first,
+                rest: Vec::new(),
+                order: None,
+                limit: 0usize,
+                offset: 0usize,
+                ctes: None,
+            }
+        }
+
+        /// Add the rows of another query, without duplicates (`UNION`)
+        pub fn union(&mut self, query: Select<'a>) -> &mut Self {
+            self.combine(SetOperation::Union, query)
+        }
+
+        /// Add the rows of another query, with duplicates (`UNION ALL`)
+        pub fn union_all(&mut self, query: Select<'a>) -> &mut Self {
+            self.combine(SetOperation::UnionAll, query)
+        }
+
+        /// Keep only the rows that another query also returns (`INTERSECT`)
+        pub fn intersect(&mut self, query: Select<'a>) -> &mut Self {
+            self.combine(SetOperation::Intersect, query)
+        }
+
+        /// Remove the rows that another query returns (`EXCEPT`)
+        pub fn except(&mut self, query: Select<'a>) -> &mut Self {
+            self.combine(SetOperation::Except, query)
+        }
+
+        /// Combine the rows so far with the rows of another query
+        pub fn combine(&mut self, operation: SetOperation, query: Select<'a>) -> &mut Self {
+            self.rest.push((operation, query));
+            self
+        }
+
+        /// Order the combined result set based on the value of an expression (`ORDER BY` clause)
+        pub fn order_by<E: Into<Expr<'a>>>(&mut self, expr: E, direction: Order) -> &mut Self {
+            self.order_by_key(OrderBy::new(expr, direction))
+        }
+
+        /// Order the combined result set by a sort key, with its options (`ORDER BY` clause)
+        pub fn order_by_key(&mut self, key: OrderBy<'a>) -> &mut Self {
+            if self.order.is_none() {
+                self.order = Some(Vec::new());
+            }
+
+            match self.order {
+                Some(ref mut current_order) => {
+                    current_order.push(key);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Limit number of rows in the combined result set (`LIMIT`)
+        pub fn limit(&mut self, limit: usize) -> &mut Self {
+            self.limit = limit;
+            self
+        }
+
+        /// Offset number of rows in the combined result set (`OFFSET`)
+        pub fn offset(&mut self, offset: usize) -> &mut Self {
+            self.offset = offset;
+            self
+        }
+
+        /// Add a named subquery that the query can refer to as a table (`WITH` clause)
+        ///
+        /// Data-modifying subqueries (`INSERT`, `UPDATE` and `DELETE`) are only supported by
+        /// PostgreSQL.
+        pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: None,
+                recursive: false,
+                query: query.into(),
+            })
+        }
+
+        /// Add a named subquery that can refer to itself, with the names of its columns
+        /// (`WITH RECURSIVE` clause)
+        pub fn with_recursive<N, Q>(&mut self, name: N, columns: &[&'a str], query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: Some(columns.iter().map(|c| Ident::new(*c)).collect()),
+                recursive: true,
+                query: query.into(),
+            })
+        }
+
+        fn push_cte(&mut self, cte: Cte<'a>) -> &mut Self {
+            if self.ctes.is_none() {
+                self.ctes = Some(Vec::new());
+            }
+
+            match self.ctes {
+                Some(ref mut current_ctes) => {
+                    current_ctes.push(cte);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            let mut expected = self.first.column_count();
+            for (_, select) in self.rest.iter() {
+                match (expected, select.column_count()) {
+                    (Some(expected), Some(found)) if expected != found => {
+                        return Err(BuildError::ColumnCountMismatch { expected, found });
+                    }
+                    (None, found) => expected = found,
+                    _ => {}
+                }
+            }
+
+            if let Some(ref ctes) = self.ctes {
+                query.push_ctes(ctes)?;
+            }
+
+            self.write_member(query, &self.first)?;
+            for (operation, select) in self.rest.iter() {
+                query.push(operation.as_str());
+                self.write_member(query, select)?;
+            }
+
+            if let Some(ref order) = self.order {
+                query.push(" ORDER BY ");
+                query.push_order(order)?;
+            }
+
+            query.push_limit(self.limit, self.offset);
+
+            Ok(())
+        }
+
+        /// Append one of the combined queries, in parentheses if it has clauses of its own that
+        /// would otherwise apply to the combined result set
+        fn write_member(&self, query: &mut Writer, select: &Select) -> Result<(), BuildError> {
+            if select.locks.is_some() {
+                return Err(BuildError::Incompatible {
+                    clause: "FOR UPDATE or FOR SHARE",
+                    with: "UNION, INTERSECT or EXCEPT",
+                });
+            }
+
+            let bare = select.order.is_none()
+                && select.limit == 0
+                && select.offset == 0
+                && select.ctes.is_none();
+
+            if bare {
+                select.write(query)
+            } else if query.dialect == Dialect::Sqlite {
+                Err(query.unsupported("ORDER BY, LIMIT or WITH in a compound SELECT"))
+            } else {
+                query.push("(");
+                select.write(query)?;
+                query.push(")");
+                Ok(())
+            }
+        }
+    }
+
+    impl<'a> CreateIndex<'a> {
+        /// Construct a new `CREATE INDEX` query builder, for an index of `table` named `name`
+        pub fn new<N: Into<Ident<'a>>, T: Into<Ident<'a>>>(name: N, table: T) -> Self {
+            CreateIndex {
+                name: name.into(),
+                table: table.into(),
+                unique: false,
+                concurrently: false,
+                if_not_exists: false,
+                method: None,
+                keys: Vec::new(),
+                conditions: None,
+            }
+        }
+
+        /// Require rows to have distinct values of the index's keys (`UNIQUE`)
+        pub fn unique(&mut self) -> &mut Self {
+            self.unique = true;
+            self
+        }
+
+        /// Build the index without locking the table against writes (`CONCURRENTLY`)
+        pub fn concurrently(&mut self) -> &mut Self {
+            self.concurrently = true;
+            self
+        }
+
+        /// Do nothing if the index already exists (`IF NOT EXISTS`)
+        pub fn if_not_exists(&mut self) -> &mut Self {
+            self.if_not_exists = true;
+            self
+        }
+
+        /// Set the data structure of the index (`USING`)
+        pub fn using(&mut self, method: IndexMethod) -> &mut Self {
+            self.method = Some(method);
+            self
+        }
+
+        /// Add a column to the index's keys
+        pub fn column<C: Into<Ident<'a>>>(&mut self, column: C, direction: Order) -> &mut Self {
+            self.key(OrderBy::new(Expr::Column(column.into()), direction))
+        }
+
+        /// Add the value of an expression to the index's keys (e.g., `lower(email)`)
+        pub fn expression<E: Into<Expr<'a>>>(&mut self, expr: E, direction: Order) -> &mut Self {
+            self.key(OrderBy::new(expr, direction))
+        }
+
+        /// Add a key, with its options (e.g., `COLLATE`)
+        pub fn key(&mut self, key: OrderBy<'a>) -> &mut Self {
+            self.keys.push(key);
+            self
+        }
+
+        /// Only index rows that meet conditions (`WHERE` clause), making a partial index
+        pub fn filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            if self.conditions.is_none() {
+                self.conditions = Some(Vec::new());
+            }
+
+            match self.conditions {
+                Some(ref mut current_conditions) => {
+                    current_conditions.push(expr.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls
+        ///
+        /// Values are written into the query as literals, so none are returned.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid; use `try_build` to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls, or
+        /// an error if the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            let dialect = query.dialect;
+
+            if self.keys.is_empty() {
+                return Err(BuildError::EmptyColumns {
+                    clause: "CREATE INDEX",
+                });
+            }
+
+            if self.concurrently && dialect != Dialect::Postgres {
+                return Err(query.unsupported("CREATE INDEX CONCURRENTLY"));
+            }
+
+            if self.if_not_exists && dialect == Dialect::MySql {
+                return Err(query.unsupported("CREATE INDEX IF NOT EXISTS"));
+            }
+
+            if self.conditions.is_some() && dialect == Dialect::MySql {
+                return Err(query.unsupported("partial indexes"));
+            }
+
+            // PostgreSQL names the method before the keys, and MySQL after them; SQLite only
+            // has B-tree indexes
+            let method = match (dialect, self.method) {
+                (_, None) | (Dialect::Sqlite, Some(IndexMethod::BTree)) => None,
+                (Dialect::Postgres, Some(method)) => Some(match method {
+                    IndexMethod::BTree => "btree",
+                    IndexMethod::Hash => "hash",
+                    IndexMethod::Gin => "gin",
+                    IndexMethod::Gist => "gist",
+                    IndexMethod::Brin => "brin",
+                }),
+                (Dialect::MySql, Some(IndexMethod::BTree)) => Some("BTREE"),
+                (Dialect::MySql, Some(IndexMethod::Hash)) => Some("HASH"),
+                (_, Some(_)) => return Err(query.unsupported("this index method")),
+            };
+
+            query.literals = true;
+
+            query.push("CREATE ");
+            if self.unique {
+                query.push("UNIQUE ");
+            }
+            query.push("INDEX ");
+            if self.concurrently {
+                query.push("CONCURRENTLY ");
+            }
+            if self.if_not_exists {
+                query.push("IF NOT EXISTS ");
+            }
+            query.push_ident(&self.name);
+            query.push(" ON ");
+            query.push_ident(&self.table);
+
+            if let (Dialect::Postgres, Some(method)) = (dialect, method) {
+                query.push(" USING ");
+                query.push(method);
+            }
+
+            query.push(" (");
+            query.push_index_keys(&self.keys)?;
+            query.push(")");
+
+            if let (Dialect::MySql, Some(method)) = (dialect, method) {
+                query.push(" USING ");
+                query.push(method);
+            }
+
+            if let Some(ref conditions) = self.conditions {
+                query.push(" WHERE ");
+                query.push_conditions(conditions)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> CreateTable<'a> {
+        /// Construct a new `CREATE TABLE` query builder
+        pub fn new<T: Into<Ident<'a>>>(table: T) -> Self {
+            CreateTable {
+                table: table.into(),
+                if_not_exists: false,
+                columns: Vec::new(),
+                constraints: None,
+            }
+        }
+
+        /// Do nothing if the table already exists (`IF NOT EXISTS`)
+        pub fn if_not_exists(&mut self) -> &mut Self {
+            self.if_not_exists = true;
+            self
+        }
+
+        /// Add a column definition
+        pub fn column(&mut self, column: Column<'a>) -> &mut Self {
+            self.columns.push(column);
+            self
+        }
+
+        /// Identify rows by the values of columns (`PRIMARY KEY` constraint)
+        pub fn primary_key(&mut self, columns: &[&'a str]) -> &mut Self {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.push_constraint(TableConstraint::PrimaryKey(columns))
+        }
+
+        /// Require rows to have distinct values of columns (`UNIQUE` constraint)
+        pub fn unique(&mut self, columns: &[&'a str]) -> &mut Self {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.push_constraint(TableConstraint::Unique(columns))
+        }
+
+        /// Require rows to meet a condition (`CHECK` constraint)
+        pub fn check<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_constraint(TableConstraint::Check(expr.into()))
+        }
+
+        /// Require the values of columns to match a row of another table (`FOREIGN KEY`
+        /// constraint)
+        pub fn foreign_key(
+            &mut self,
+            columns: &[&'a str],
+            references: References<'a>,
+        ) -> &mut Self {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.push_constraint(TableConstraint::ForeignKey(columns, references))
+        }
+
+        fn push_constraint(&mut self, constraint: TableConstraint<'a>) -> &mut Self {
+            if self.constraints.is_none() {
+                self.constraints = Some(Vec::new());
+            }
+
+            match self.constraints {
+                Some(ref mut current_constraints) => {
+                    current_constraints.push(constraint);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls
+        ///
+        /// Values are written into the query as literals, so none are returned.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid; use `try_build` to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls, or
+        /// an error if the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            if self.columns.is_empty() {
+                return Err(BuildError::EmptyColumns {
+                    clause: "CREATE TABLE",
+                });
+            }
+
+            let mut primary_keys = self.columns.iter().filter(|c| c.primary_key).count();
+            if let Some(ref constraints) = self.constraints {
+                primary_keys += constraints
+                    .iter()
+                    .filter(|c| matches!(**c, TableConstraint::PrimaryKey(_)))
+                    .count();
+            }
+            if primary_keys > 1 {
+                return Err(BuildError::Incompatible {
+                    clause: "PRIMARY KEY",
+                    with: "another PRIMARY KEY",
+                });
+            }
+
+            query.literals = true;
+
+            query.push("CREATE TABLE ");
+            if self.if_not_exists {
+                query.push("IF NOT EXISTS ");
+            }
+            query.push_ident(&self.table);
+            query.push(" (");
+
+            for (i, column) in self.columns.iter().enumerate() {
+                if i != 0 {
+                    query.push(", ");
+                }
+                query.push_column(column)?;
+            }
+
+            if let Some(ref constraints) = self.constraints {
+                for constraint in constraints {
+                    query.push(", ");
+                    query.push_table_constraint(constraint)?;
+                }
+            }
+
+            if query.dialect == Dialect::MySql {
+                for column in &self.columns {
+                    if let Some(ref references) = column.references {
+                        query.push(", FOREIGN KEY (");
+                        query.push_ident(&column.name);
+                        query.push(") ");
+                        query.push_references(references, 1)?;
+                    }
+                }
+            }
+
+            query.push(")");
+            Ok(())
+        }
+    }
+
+    impl<'a> Delete<'a> {
+        /// Construct a new `DELETE` query builder
+        pub fn new<T: Into<Ident<'a>>>(table: T) -> Self {
+            Delete {
+                table: table.into(),
+                joins: None,
+                conditions: None,
+                returns: None,
+                ctes: None,
+            }
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause)
+        pub fn filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_condition(expr.into())
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause), binding each `?` in `expr`
+        /// to the corresponding value
+        ///
+        /// Building the query returns an error if the number of `?` markers in `expr` is not equal
+        /// to the number of `values`.
+        pub fn filter_with<S: Into<Cow<'a, str>>>(
+            &mut self,
+            expr: S,
+            values: Vec<Value>,
+        ) -> &mut Self {
+            self.push_condition(Expr::Bound(expr.into(), values))
+        }
+
+        fn push_condition(&mut self, condition: Expr<'a>) -> &mut Self {
+            if self.conditions.is_none() {
+                self.conditions = Some(Vec::new());
+            }
+
+            match self.conditions {
+                Some(ref mut current_conditions) => {
+                    current_conditions.push(condition);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Apply a scope's conditions, as a single group
+        ///
+        /// A scope's ordering does not change which rows are affected, so it is ignored. `DELETE`
+        /// queries cannot join other tables; building a query with a scope that has joins returns
+        /// an error.
+        pub fn scope<S: IntoScope<'a>>(&mut self, scope: S) -> &mut Self {
+            let scope = scope.into_scope();
+
+            if !scope.conditions.is_empty() {
+                self.push_condition(Expr::And(scope.conditions));
+            }
+
+            if !scope.joins.is_empty() {
+                if self.joins.is_none() {
+                    self.joins = Some(Vec::new());
+                }
+
+                match self.joins {
+                    Some(ref mut current_joins) => {
+                        current_joins.extend(scope.joins);
+                    }
+                    None => unreachable!(),
+                }
+            }
+
+            self
+        }
+
+        /// Add returning field
+        pub fn returning<E: Into<Expr<'a>>>(&mut self, field: E) -> &mut Self {
+            if self.returns.is_none() {
+                self.returns = Some(Vec::new());
+            }
+
+            match self.returns {
+                Some(ref mut current_returns) => {
+                    current_returns.push(field.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Add a named subquery that the query can refer to as a table (`WITH` clause)
+        ///
+        /// Data-modifying subqueries (`INSERT`, `UPDATE` and `DELETE`) are only supported by
+        /// PostgreSQL.
+        pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: None,
+                recursive: false,
+                query: query.into(),
+            })
+        }
+
+        /// Add a named subquery that can refer to itself, with the names of its columns
+        /// (`WITH RECURSIVE` clause)
+        pub fn with_recursive<N, Q>(&mut self, name: N, columns: &[&'a str], query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: Some(columns.iter().map(|c| Ident::new(*c)).collect()),
+                recursive: true,
+                query: query.into(),
+            })
+        }
+
+        fn push_cte(&mut self, cte: Cte<'a>) -> &mut Self {
+            if self.ctes.is_none() {
+                self.ctes = Some(Vec::new());
+            }
+
+            match self.ctes {
+                Some(ref mut current_ctes) => {
+                    current_ctes.push(cte);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            if let Some(ref ctes) = self.ctes {
+                query.push_ctes(ctes)?;
+            }
+
+            query.push("DELETE FROM ");
+            query.push_ident(&self.table);
+
+            if self.joins.is_some() {
+                return Err(BuildError::Incompatible {
+                    clause: "JOIN",
+                    with: "DELETE",
+                });
+            }
+
+            if let Some(ref conditions) = self.conditions {
+                query.push(" WHERE ");
+                query.push_conditions(conditions)?;
+            }
+
+            if let Some(ref returns) = self.returns {
+                query.push_returning(returns)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> DropObject<'a> {
+        fn new(kind: ObjectKind, name: Ident<'a>) -> Self {
+            DropObject {
+                kind,
+                name,
+                table: None,
+                if_exists: false,
+                cascade: false,
+            }
+        }
+
+        /// Construct a new `DROP TABLE` query builder
+        pub fn table<T: Into<Ident<'a>>>(table: T) -> Self {
+            DropObject::new(ObjectKind::Table, table.into())
+        }
+
+        /// Construct a new `DROP INDEX` query builder
+        pub fn index<N: Into<Ident<'a>>>(index: N) -> Self {
+            DropObject::new(ObjectKind::Index, index.into())
+        }
+
+        /// Construct a new `DROP VIEW` query builder
+        pub fn view<V: Into<Ident<'a>>>(view: V) -> Self {
+            DropObject::new(ObjectKind::View, view.into())
+        }
+
+        /// Do nothing if the object does not exist (`IF EXISTS`)
+        pub fn if_exists(&mut self) -> &mut Self {
+            self.if_exists = true;
+            self
+        }
+
+        /// Also drop the objects that depend on this one (`CASCADE`)
+        pub fn cascade(&mut self) -> &mut Self {
+            self.cascade = true;
+            self
+        }
+
+        /// Set the table an index belongs to, which MySQL requires (`ON`)
+        pub fn on<T: Into<Ident<'a>>>(&mut self, table: T) -> &mut Self {
+            self.table = Some(table.into());
+            self
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid; use `try_build` to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls, or
+        /// an error if the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`), with no bound values, from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            query.push(match self.kind {
+                ObjectKind::Table => "DROP TABLE ",
+                ObjectKind::Index => "DROP INDEX ",
+                ObjectKind::View => "DROP VIEW ",
+            });
+
+            if self.if_exists {
+                if self.kind == ObjectKind::Index && query.dialect == Dialect::MySql {
+                    return Err(query.unsupported("DROP INDEX IF EXISTS"));
+                }
+                query.push("IF EXISTS ");
+            }
+
+            query.push_ident(&self.name);
+
+            if self.kind == ObjectKind::Index && query.dialect == Dialect::MySql {
+                match self.table {
+                    Some(ref table) => {
+                        query.push(" ON ");
+                        query.push_ident(table);
+                    }
+                    None => {
+                        return Err(BuildError::MissingClause {
+                            clause: "DROP INDEX",
+                            requires: "ON",
+                        });
+                    }
+                }
+            }
+
+            if self.cascade {
+                if query.dialect != Dialect::Postgres {
+                    return Err(query.unsupported("CASCADE"));
+                }
+                query.push(" CASCADE");
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> Insert<'a> {
+        /// Construct a new `INSERT` query builder
+        pub fn new<T: Into<Ident<'a>>>(table: T) -> Self {
+            Insert {
+                table: table.into(),
+                values: Assignments(Vec::new()),
+                columns: None,
+                rows: None,
+                source: None,
+                conflict: None,
+                returns: None,
+                ctes: None,
+            }
+        }
+
+        /// Set a field value, bound as a query parameter (or an expression, used as-is)
+        ///
+        /// Fields are written in the order they are first set. The values set form the first row
+        /// of the `INSERT`.
+        pub fn set<F: Assign<'a, V>, V>(&mut self, field: F, value: V) -> &mut Self {
+            let (field, value) = field.assign(value);
+            self.values.set(field, value);
+            self
+        }
+
+        /// Specify the columns of the rows added with `row` and `rows`
+        pub fn columns(&mut self, columns: &[&'a str]) -> &mut Self {
+            self.columns = Some(columns.iter().map(|c| Ident::from(*c)).collect());
+            self
+        }
+
+        /// Add a row of values, in the order of `columns`
+        pub fn row<I, O>(&mut self, values: I) -> &mut Self
+        where
+            I: IntoIterator<Item = O>,
+            O: Operand<'a>,
+        {
+            let values = values.into_iter().map(Operand::into_expr).collect();
+            self.push_row(InsertRow::Positional(values))
+        }
+
+        /// Add rows of values, each in the order of `columns`
+        pub fn rows<R, I, O>(&mut self, rows: R) -> &mut Self
+        where
+            R: IntoIterator<Item = I>,
+            I: IntoIterator<Item = O>,
+            O: Operand<'a>,
+        {
+            for values in rows {
+                self.row(values);
+            }
+            self
+        }
+
+        /// Insert the rows of a `SELECT` query (`INSERT ... SELECT`), in the order of `columns`
+        ///
+        /// Rows cannot also be added with `set`, `row` or `record`.
+        pub fn select(&mut self, query: Select<'a>) -> &mut Self {
+            self.source = Some(query);
+            self
+        }
+
+        /// Add a row from a value whose fields map to columns
+        pub fn record<R: Row<'a>>(&mut self, record: R) -> &mut Self {
+            let mut values = Assignments(Vec::new());
+            for (field, value) in record.into_fields() {
+                values.set(Ident::from(field), value);
+            }
+            self.push_row(InsertRow::Named(values))
+        }
+
+        /// Add rows from values whose fields map to columns
+        pub fn records<I, R>(&mut self, records: I) -> &mut Self
+        where
+            I: IntoIterator<Item = R>,
+            R: Row<'a>,
+        {
+            for record in records {
+                self.record(record);
+            }
+            self
+        }
+
+        /// Handle rows that violate a unique index on `columns` (`ON CONFLICT` clause)
+        ///
+        /// With no columns, rows that violate any unique index are handled. MySQL always checks
+        /// every unique index (`ON DUPLICATE KEY UPDATE`), so the columns are not written.
+        /// Conflicting rows are skipped (`DO NOTHING`) unless `do_update` is called.
+        pub fn on_conflict(&mut self, columns: &[&'a str]) -> &mut Self {
+            let target = if columns.is_empty() {
+                ConflictTarget::Any
+            } else {
+                ConflictTarget::Columns(columns.iter().map(|c| Ident::from(*c)).collect())
+            };
+            self.conflict_mut().target = target;
+            self
+        }
+
+        /// Handle rows that violate the named constraint (`ON CONFLICT ON CONSTRAINT`)
+        ///
+        /// Not supported by SQLite; MySQL checks every unique index instead.
+        pub fn on_conflict_constraint<N: Into<Ident<'a>>>(&mut self, name: N) -> &mut Self {
+            self.conflict_mut().target = ConflictTarget::Constraint(name.into());
+            self
+        }
+
+        /// Skip rows that conflict (`DO NOTHING`), discarding any `do_update` values
+        pub fn do_nothing(&mut self) -> &mut Self {
+            let conflict = self.conflict_mut();
+            conflict.values = Assignments(Vec::new());
+            conflict.conditions = None;
+            self
+        }
+
+        /// Set a field of the existing row when a row conflicts (`DO UPDATE SET` clause)
+        ///
+        /// Use `excluded` to refer to the value proposed for insertion.
+        pub fn do_update<F: Assign<'a, V>, V>(&mut self, field: F, value: V) -> &mut Self {
+            let (field, value) = field.assign(value);
+            self.conflict_mut().values.set(field, value);
+            self
+        }
+
+        /// Only update conflicting rows that meet conditions (`DO UPDATE ... WHERE` clause)
+        ///
+        /// Not supported by MySQL.
+        pub fn do_update_filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            let conflict = self.conflict_mut();
+            if conflict.conditions.is_none() {
+                conflict.conditions = Some(Vec::new());
+            }
+
+            match conflict.conditions {
+                Some(ref mut current_conditions) => {
+                    current_conditions.push(expr.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        fn conflict_mut(&mut self) -> &mut OnConflict<'a> {
+            if self.conflict.is_none() {
+                self.conflict = Some(OnConflict {
+                    target: ConflictTarget::Any,
+                    values: Assignments(Vec::new()),
+                    conditions: None,
+                });
+            }
+
+            match self.conflict {
+                Some(ref mut conflict) => conflict,
+                None => unreachable!(),
+            }
+        }
+
+        fn push_row(&mut self, row: InsertRow<'a>) -> &mut Self {
+            if self.rows.is_none() {
+                self.rows = Some(Vec::new());
+            }
+
+            match self.rows {
+                Some(ref mut current_rows) => {
+                    current_rows.push(row);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Set a field to a SQL expression, written verbatim (e.g., `NOW()`)
+        pub fn set_raw<F, S>(&mut self, field: F, expr: S) -> &mut Self
+        where
+            F: Into<Ident<'a>>,
+            S: Into<Cow<'a, str>>,
+        {
+            self.values.set(field.into(), Expr::Raw(expr.into()));
+            self
+        }
+
+        /// Remove a field value that was previously set
+        pub fn unset<F: Into<Ident<'a>>>(&mut self, field: F) -> &mut Self {
+            self.values.unset(&field.into());
+            self
+        }
+
+        /// Move fields to the front, in the given order; other fields keep their relative order
+        pub fn reorder(&mut self, fields: &[&'a str]) -> &mut Self {
+            let fields: Vec<Ident> = fields.iter().map(|f| Ident::from(*f)).collect();
+            self.values.reorder(&fields);
+            self
+        }
+
+        /// Add returning field
+        pub fn returning<E: Into<Expr<'a>>>(&mut self, field: E) -> &mut Self {
+            if self.returns.is_none() {
+                self.returns = Some(Vec::new());
+            }
+
+            match self.returns {
+                Some(ref mut current_returns) => {
+                    current_returns.push(field.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Add a named subquery that the query can refer to as a table (`WITH` clause)
+        ///
+        /// Data-modifying subqueries (`INSERT`, `UPDATE` and `DELETE`) are only supported by
+        /// PostgreSQL.
+        pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: None,
+                recursive: false,
+                query: query.into(),
+            })
+        }
+
+        /// Add a named subquery that can refer to itself, with the names of its columns
+        /// (`WITH RECURSIVE` clause)
+        pub fn with_recursive<N, Q>(&mut self, name: N, columns: &[&'a str], query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: Some(columns.iter().map(|c| Ident::new(*c)).collect()),
+                recursive: true,
+                query: query.into(),
+            })
+        }
+
+        fn push_cte(&mut self, cte: Cte<'a>) -> &mut Self {
+            if self.ctes.is_none() {
+                self.ctes = Some(Vec::new());
+            }
+
+            match self.ctes {
+                Some(ref mut current_ctes) => {
+                    current_ctes.push(cte);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            // MySQL only accepts `WITH` as part of the `SELECT` of an `INSERT ... SELECT`
+            if let Some(ref ctes) = self.ctes {
+                if query.dialect != Dialect::MySql {
+                    query.push_ctes(ctes)?;
+                } else if self.source.is_none() {
+                    return Err(query.unsupported("WITH in INSERT ... VALUES"));
+                }
+            }
+
+            query.push("INSERT INTO ");
+            query.push_ident(&self.table);
+
+            let no_rows = Vec::new();
+            let rows = self.rows.as_ref().unwrap_or(&no_rows);
+
+            // Columns are given explicitly, or by the first row that names its fields
+            let columns: Vec<&Ident> = match self.columns {
+                Some(ref columns) => columns.iter().collect(),
+                None if !self.values.0.is_empty() => self.values.0.iter().map(|(f, _)| f).collect(),
+                None => match rows.first() {
+                    Some(InsertRow::Named(values)) => values.0.iter().map(|(f, _)| f).collect(),
+                    _ => Vec::new(),
+                },
+            };
+
+            let mut values: Vec<Vec<&Expr>> = Vec::with_capacity(rows.len() + 1);
+            if !self.values.0.is_empty() {
+                match self.values.ordered_by(&columns) {
+                    Some(row) => values.push(row),
+                    None => return Err(BuildError::RowMismatch { row: 0 }),
+                }
+            }
+            for row in rows {
+                match row.values(&columns) {
+                    Some(row) => values.push(row),
+                    None => return Err(BuildError::RowMismatch { row: values.len() }),
+                }
+            }
+
+            if self.source.is_none() && values.is_empty() {
+                return Err(BuildError::MissingClause {
+                    clause: "INSERT",
+                    requires: "VALUES or SELECT",
+                });
+            }
+            if columns.is_empty() && (self.source.is_none() || self.columns.is_some()) {
+                return Err(BuildError::EmptyColumns { clause: "INSERT" });
+            }
+
+            query.push(" (");
+            query.push_idents(columns.iter().cloned(), ", ");
+            query.push(")");
+
+            if let Some(ref source) = self.source {
+                if !values.is_empty() {
+                    return Err(BuildError::Incompatible {
+                        clause: "INSERT ... SELECT",
+                        with: "VALUES",
+                    });
+                }
+                if self.columns.is_none() {
+                    return Err(BuildError::MissingColumns);
+                }
+                if let Some(found) = source.column_count() {
+                    if found != columns.len() {
+                        return Err(BuildError::ColumnCountMismatch {
+                            expected: columns.len(),
+                            found,
+                        });
+                    }
+                }
+
+                // SQLite would parse `ON CONFLICT` as part of a join without a `WHERE` clause
+                let guard = query.dialect == Dialect::Sqlite && self.conflict.is_some();
+                query.push(" ");
+                if let Some(ref ctes) = self.ctes {
+                    if query.dialect == Dialect::MySql {
+                        query.push_ctes(ctes)?;
+                    }
+                }
+                source.write_select(query, guard)?;
+            }
+
+            if self.source.is_none() {
+                query.push(" VALUES ");
+            }
+            for (i, row) in values.into_iter().enumerate() {
+                if i != 0 {
+                    query.push(", ");
+                }
+                query.push("(");
+                for (j, value) in row.into_iter().enumerate() {
+                    if j != 0 {
+                        query.push(", ");
+                    }
+                    query.push_expr(value)?;
+                }
+                query.push(")");
+            }
+
+            if let Some(ref conflict) = self.conflict {
+                query.push_conflict(conflict, &columns)?;
+            }
+
+            if let Some(ref returns) = self.returns {
+                query.push_returning(returns)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> Select<'a> {
+        /// Construct a new `SELECT` query builder
+        pub fn new<T: Into<Table<'a>>>(table: T) -> Self {
+            Select {
+                table: table.into(),
+                aliases: None,
+                fields: None,
+                distinct: None,
+                order: None,
+                joins: None,
+                conditions: None,
+                groupings: None,
+                havings: None,
+                windows: None,
+                limit: 0usize,
+                offset: 0usize,
+                locks: None,
+                ctes: None,
+            }
+        }
+
+        /// Set a table alias (`AS`)
+        pub fn alias<T, S>(&mut self, table: T, alias: S) -> &mut Self
+        where
+            T: Into<Ident<'a>>,
+            S: Into<Cow<'a, str>>,
+        {
+            if self.aliases.is_none() {
+                self.aliases = Some(Vec::new());
+            }
+
+            match self.aliases {
+                Some(ref mut aliases) => {
+                    let table = table.into();
+                    aliases.retain(|(t, _)| *t != table);
+                    aliases.push((table, Ident::new(alias)));
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Remove duplicate rows from result set (`DISTINCT`)
+        pub fn distinct(&mut self) -> &mut Self {
+            self.distinct = Some(Distinct::Rows);
+            self
+        }
+
+        /// Keep only the first row of each set of rows with equal values of `exprs`
+        /// (`DISTINCT ON`)
+        ///
+        /// Only supported by PostgreSQL. If the result set is ordered, `exprs` must be the
+        /// leftmost `ORDER BY` expressions.
+        pub fn distinct_on<I, E>(&mut self, exprs: I) -> &mut Self
+        where
+            I: IntoIterator<Item = E>,
+            E: Into<Expr<'a>>,
+        {
+            self.distinct = Some(Distinct::On(exprs.into_iter().map(Into::into).collect()));
+            self
+        }
+
+        /// Specify desired table fields in result set
+        pub fn fields(&mut self, fields: &[&'a str]) -> &mut Self {
+            if self.fields.is_none() {
+                self.fields = Some(Vec::new());
+            }
+
+            match self.fields {
+                Some(ref mut current_fields) => {
+                    for field in fields {
+                        current_fields.push((Expr::from(*field), None));
+                    }
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Add an expression (e.g., a subquery) to the fields in result set
+        pub fn field<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_field(expr.into(), None)
+        }
+
+        /// Add an expression (e.g., a subquery) to the fields in result set, with an alias (`AS`)
+        pub fn field_as<E, S>(&mut self, expr: E, alias: S) -> &mut Self
+        where
+            E: Into<Expr<'a>>,
+            S: Into<Cow<'a, str>>,
+        {
+            self.push_field(expr.into(), Some(Ident::new(alias)))
+        }
+
+        fn push_field(&mut self, expr: Expr<'a>, alias: Option<Ident<'a>>) -> &mut Self {
+            if self.fields.is_none() {
+                self.fields = Some(Vec::new());
+            }
+
+            match self.fields {
+                Some(ref mut current_fields) => {
+                    current_fields.push((expr, alias));
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause)
+        pub fn filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_condition(expr.into())
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause), binding each `?` in `expr`
+        /// to the corresponding value
+        ///
+        /// Building the query returns an error if the number of `?` markers in `expr` is not equal
+        /// to the number of `values`.
+        pub fn filter_with<S: Into<Cow<'a, str>>>(
+            &mut self,
+            expr: S,
+            values: Vec<Value>,
+        ) -> &mut Self {
+            self.push_condition(Expr::Bound(expr.into(), values))
+        }
+
+        fn push_condition(&mut self, condition: Expr<'a>) -> &mut Self {
+            if self.conditions.is_none() {
+                self.conditions = Some(Vec::new());
+            }
+
+            match self.conditions {
+                Some(ref mut current_conditions) => {
+                    current_conditions.push(condition);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Group result set based on common value (`GROUP BY` clause)
+        pub fn group_by<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            if self.groupings.is_none() {
+                self.groupings = Some(Vec::new());
+            }
+
+            match self.groupings {
+                Some(ref mut current_groupings) => {
+                    current_groupings.push(expr.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Filter result set based on an expression on an aggregate value (`HAVING` clause)
+        pub fn having<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_having(expr.into())
+        }
+
+        /// Filter result set based on an expression on an aggregate value (`HAVING` clause),
+        /// binding each `?` in `expr` to the corresponding value
+        ///
+        /// Building the query returns an error if the number of `?` markers in `expr` is not equal
+        /// to the number of `values`.
+        pub fn having_with<S: Into<Cow<'a, str>>>(
+            &mut self,
+            expr: S,
+            values: Vec<Value>,
+        ) -> &mut Self {
+            self.push_having(Expr::Bound(expr.into(), values))
+        }
+
+        fn push_having(&mut self, having: Expr<'a>) -> &mut Self {
+            if self.havings.is_none() {
+                self.havings = Some(Vec::new());
+            }
+
+            match self.havings {
+                Some(ref mut current_havings) => {
+                    current_havings.push(having);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Define a named window that window functions can be evaluated over (`WINDOW` clause)
+        pub fn window<S: Into<Cow<'a, str>>>(&mut self, name: S, window: Window<'a>) -> &mut Self {
+            if self.windows.is_none() {
+                self.windows = Some(Vec::new());
+            }
+
+            match self.windows {
+                Some(ref mut current_windows) => {
+                    current_windows.push((Ident::new(name), window));
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Order result set based on the value of an expression (`ORDER BY` clause)
+        pub fn order_by<E: Into<Expr<'a>>>(&mut self, expr: E, direction: Order) -> &mut Self {
+            self.order_by_key(OrderBy::new(expr, direction))
+        }
+
+        /// Order result set by a sort key, with its options (`ORDER BY` clause)
+        pub fn order_by_key(&mut self, key: OrderBy<'a>) -> &mut Self {
+            if self.order.is_none() {
+                self.order = Some(Vec::new());
+            }
+
+            match self.order {
+                Some(ref mut current_order) => {
+                    current_order.push(key);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on the equality of two expressions
+        pub fn join<T, L, R>(&mut self, table: T, on_left: L, on_right: R, kind: Join) -> &mut Self
+        where
+            T: Into<Table<'a>>,
+            L: Into<Expr<'a>>,
+            R: Into<Expr<'a>>,
+        {
+            let condition = on_left.into().eq(on_right.into());
+            self.push_join(table.into(), JoinConstraint::On(condition), kind)
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on an arbitrary condition
+        pub fn join_on<T: Into<Table<'a>>, E: Into<Expr<'a>>>(
+            &mut self,
+            table: T,
+            condition: E,
+            kind: Join,
+        ) -> &mut Self {
+            self.push_join(table.into(), JoinConstraint::On(condition.into()), kind)
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on the equality of columns that both
+        /// tables have (`USING`)
+        pub fn join_using<T: Into<Table<'a>>>(
+            &mut self,
+            table: T,
+            columns: &[&'a str],
+            kind: Join,
+        ) -> &mut Self {
+            let columns = columns.iter().map(|c| Ident::new(*c)).collect();
+            self.push_join(table.into(), JoinConstraint::Using(columns), kind)
+        }
+
+        /// Join another table (or subquery) (`JOIN` clause) on the equality of all columns that
+        /// both tables have (`NATURAL`)
+        pub fn natural_join<T: Into<Table<'a>>>(&mut self, table: T, kind: Join) -> &mut Self {
+            self.push_join(table.into(), JoinConstraint::Natural, kind)
+        }
+
+        /// Join every row of another table (or subquery) (`CROSS JOIN` clause)
+        pub fn cross_join<T: Into<Table<'a>>>(&mut self, table: T) -> &mut Self {
+            self.push_join(table.into(), JoinConstraint::None, Join::Cross)
+        }
+
+        fn push_join(
+            &mut self,
+            table: Table<'a>,
+            constraint: JoinConstraint<'a>,
+            kind: Join,
+        ) -> &mut Self {
+            if self.joins.is_none() {
+                self.joins = Some(Vec::new());
+            }
+
+            match self.joins {
+                Some(ref mut current_joins) => {
+                    let join = JoinClause {
+                        table,
+                        constraint,
+                        kind,
+                    };
+                    current_joins.push(join);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Apply a scope's conditions (as a single group), joins and ordering
+        ///
+        /// A join that the query already has is not repeated, so scopes that join the same table
+        /// can be combined.
+        pub fn scope<S: IntoScope<'a>>(&mut self, scope: S) -> &mut Self {
+            let scope = scope.into_scope();
+
+            if !scope.conditions.is_empty() {
+                self.push_condition(Expr::And(scope.conditions));
+            }
+
+            for join in scope.joins {
+                let joined = match self.joins {
+                    Some(ref joins) => joins.contains(&join),
+                    None => false,
+                };
+                if !joined {
+                    self.push_join(join.table, join.constraint, join.kind);
+                }
+            }
+
+            for key in scope.order {
+                self.order_by_key(key);
+            }
+
+            self
+        }
+
+        /// Limit number of rows in result set (`LIMIT`)
+        pub fn limit(&mut self, limit: usize) -> &mut Self {
+            self.limit = limit;
+            self
+        }
+
+        /// Offset number of rows in result set (`OFFSET`)
+        pub fn offset(&mut self, offset: usize) -> &mut Self {
+            self.offset = offset;
+            self
+        }
+
+        /// Lock the rows of result set (`FOR UPDATE` or `FOR SHARE` clause)
+        ///
+        /// Not supported by SQLite.
+        pub fn lock(&mut self, lock: Lock<'a>) -> &mut Self {
+            if self.locks.is_none() {
+                self.locks = Some(Vec::new());
+            }
+
+            match self.locks {
+                Some(ref mut current_locks) => {
+                    current_locks.push(lock);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Number of columns in the result set, if known
+        fn column_count(&self) -> Option<usize> {
+            let fields = self.fields.as_ref()?;
+            let wildcard = fields.iter().any(|field| match *field {
+                (Expr::Column(ref ident), _) => ident.parts.last().is_some_and(|p| p == "*"),
+                _ => false,
+            });
+
+            if wildcard {
+                None
+            } else {
+                Some(fields.len())
+            }
+        }
+
+        /// Add a named subquery that the query can refer to as a table (`WITH` clause)
+        ///
+        /// Data-modifying subqueries (`INSERT`, `UPDATE` and `DELETE`) are only supported by
+        /// PostgreSQL.
+        pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: None,
+                recursive: false,
+                query: query.into(),
+            })
+        }
+
+        /// Add a named subquery that can refer to itself, with the names of its columns
+        /// (`WITH RECURSIVE` clause)
+        pub fn with_recursive<N, Q>(&mut self, name: N, columns: &[&'a str], query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: Some(columns.iter().map(|c| Ident::new(*c)).collect()),
+                recursive: true,
+                query: query.into(),
+            })
+        }
+
+        fn push_cte(&mut self, cte: Cte<'a>) -> &mut Self {
+            if self.ctes.is_none() {
+                self.ctes = Some(Vec::new());
+            }
+
+            match self.ctes {
+                Some(ref mut current_ctes) => {
+                    current_ctes.push(cte);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            self.write_select(query, false)
+        }
+
+        /// Append a table (with its alias, if any) or a subquery (with its alias)
+        fn write_table(&self, query: &mut Writer, table: &Table) -> Result<(), BuildError> {
+            match *table {
+                Table::Named(ref ident) => {
+                    query.push_ident(ident);
+
+                    if let Some(ref aliases) = self.aliases {
+                        if let Some((_, alias)) = aliases.iter().find(|(t, _)| t == ident) {
+                            query.push(" AS ");
+                            query.push_ident(alias);
+                        }
+                    }
+                }
+                Table::Derived(ref subquery, ref alias) => {
+                    query.push("(");
+                    subquery.write(query)?;
+                    query.push(") AS ");
+                    query.push_ident(alias);
+                }
+                Table::Lateral(ref subquery, ref alias) => {
+                    if query.dialect == Dialect::Sqlite {
+                        return Err(query.unsupported("LATERAL"));
+                    }
+                    query.push("LATERAL (");
+                    subquery.write(query)?;
+                    query.push(") AS ");
+                    query.push_ident(alias);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Append a `JOIN` clause
+        fn write_join(&self, query: &mut Writer, join: &JoinClause) -> Result<(), BuildError> {
+            match (&join.kind, &join.constraint) {
+                (&Join::Cross, &JoinConstraint::None) => {}
+                (&Join::Cross, _) => {
+                    return Err(BuildError::Incompatible {
+                        clause: "CROSS JOIN",
+                        with: "a join condition",
+                    })
+                }
+                (&Join::Full, _) if query.dialect == Dialect::MySql => {
+                    return Err(query.unsupported("FULL OUTER JOIN"))
+                }
+                _ => {}
+            }
+
+            if let JoinConstraint::Natural = join.constraint {
+                query.push(" NATURAL");
+            }
+
+            match join.kind {
+                Join::Left => query.push(" LEFT"),
+                Join::Inner => query.push(" INNER"),
+                Join::Right => query.push(" RIGHT"),
+                Join::Full => query.push(" FULL OUTER"),
+                Join::Cross => query.push(" CROSS"),
+            }
+
+            query.push(" JOIN ");
+            self.write_table(query, &join.table)?;
+
+            match join.constraint {
+                JoinConstraint::On(ref condition) => {
+                    query.push(" ON ");
+                    query.push_expr(condition)?;
+                }
+                JoinConstraint::Using(ref columns) => {
+                    if columns.is_empty() {
+                        return Err(BuildError::EmptyColumns { clause: "USING" });
+                    }
+                    query.push(" USING (");
+                    query.push_idents(columns, ", ");
+                    query.push(")");
+                }
+                JoinConstraint::None | JoinConstraint::Natural => {}
+            }
+
+            Ok(())
+        }
+
+        /// Append the query to `query`, with a `WHERE` clause even if there are no conditions if
+        /// `always_filter` is set
+        fn write_select(&self, query: &mut Writer, always_filter: bool) -> Result<(), BuildError> {
+            if let Some(ref ctes) = self.ctes {
+                query.push_ctes(ctes)?;
+            }
+
+            query.push("SELECT ");
+
+            match self.distinct {
+                Some(Distinct::Rows) => query.push("DISTINCT "),
+                Some(Distinct::On(ref exprs)) => {
+                    if exprs.is_empty() {
+                        return Err(BuildError::EmptyColumns {
+                            clause: "DISTINCT ON",
+                        });
+                    }
+                    if query.dialect != Dialect::Postgres {
+                        return Err(query.unsupported("DISTINCT ON"));
+                    }
+                    if let Some(ref order) = self.order {
+                        if !leads_order(exprs, order, query.dialect)? {
+                            return Err(BuildError::DistinctOnOrder);
+                        }
+                    }
+
+                    query.push("DISTINCT ON (");
+                    query.push_exprs(exprs, ", ")?;
+                    query.push(") ");
+                }
+                None => {}
+            }
+
+            match self.fields {
+                Some(ref fields) if fields.is_empty() => {
+                    return Err(BuildError::EmptyColumns { clause: "SELECT" });
+                }
+                Some(ref fields) => {
+                    for (i, (expr, alias)) in fields.iter().enumerate() {
+                        if i != 0 {
+                            query.push(", ");
+                        }
+                        query.push_expr(expr)?;
+                        if let Some(alias) = alias {
+                            query.push(" AS ");
+                            query.push_ident(alias);
+                        }
+                    }
+                }
+                None => query.push("*"),
+            }
+
+            query.push(" FROM ");
+            self.write_table(query, &self.table)?;
+
+            if let Some(ref joins) = self.joins {
+                for join in joins.iter() {
+                    self.write_join(query, join)?;
+                }
+            }
+
+            match self.conditions {
+                Some(ref conditions) => {
+                    query.push(" WHERE ");
+                    query.push_conditions(conditions)?;
+                }
+                None if always_filter => query.push(" WHERE true"),
+                None => {}
+            }
+
+            if let Some(ref groupings) = self.groupings {
+                query.push(" GROUP BY ");
+                query.push_exprs(groupings, ", ")?;
+            }
+
+            if let Some(ref havings) = self.havings {
+                if self.groupings.is_none() {
+                    return Err(BuildError::MissingClause {
+                        clause: "HAVING",
+                        requires: "GROUP BY",
+                    });
+                }
+                query.push(" HAVING ");
+                query.push_conditions(havings)?;
+            }
+
+            if let Some(ref windows) = self.windows {
+                query.push(" WINDOW ");
+                for (i, (name, window)) in windows.iter().enumerate() {
+                    if i != 0 {
+                        query.push(", ");
+                    }
+                    query.push_ident(name);
+                    query.push(" AS (");
+                    query.push_window(window)?;
+                    query.push(")");
+                }
+            }
+
+            if let Some(ref order) = self.order {
+                query.push(" ORDER BY ");
+                query.push_order(order)?;
+            }
+
+            query.push_limit(self.limit, self.offset);
+
+            if let Some(ref locks) = self.locks {
+                for lock in locks.iter() {
+                    query.push_lock(lock)?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<'a> Update<'a> {
+        /// Construct a new `UPDATE` query builder
+        pub fn new<T: Into<Ident<'a>>>(table: T) -> Self {
+            Update {
+                table: table.into(),
+                values: Assignments(Vec::new()),
+                joins: None,
+                conditions: None,
+                returns: None,
+                ctes: None,
+            }
+        }
+
+        /// Set a field value, bound as a query parameter (or an expression, used as-is)
+        ///
+        /// Fields are written in the order they are first set.
+        pub fn set<F: Assign<'a, V>, V>(&mut self, field: F, value: V) -> &mut Self {
+            let (field, value) = field.assign(value);
+            self.values.set(field, value);
+            self
+        }
+
+        /// Set a field to a SQL expression, written verbatim (e.g., `NOW()`)
+        pub fn set_raw<F, S>(&mut self, field: F, expr: S) -> &mut Self
+        where
+            F: Into<Ident<'a>>,
+            S: Into<Cow<'a, str>>,
+        {
+            self.values.set(field.into(), Expr::Raw(expr.into()));
+            self
+        }
+
+        /// Remove a field value that was previously set
+        pub fn unset<F: Into<Ident<'a>>>(&mut self, field: F) -> &mut Self {
+            self.values.unset(&field.into());
+            self
+        }
+
+        /// Move fields to the front, in the given order; other fields keep their relative order
+        pub fn reorder(&mut self, fields: &[&'a str]) -> &mut Self {
+            let fields: Vec<Ident> = fields.iter().map(|f| Ident::from(*f)).collect();
+            self.values.reorder(&fields);
+            self
+        }
+
+        /// Add returning field
+        pub fn returning<E: Into<Expr<'a>>>(&mut self, field: E) -> &mut Self {
+            if self.returns.is_none() {
+                self.returns = Some(Vec::new());
+            }
+
+            match self.returns {
+                Some(ref mut current_returns) => {
+                    current_returns.push(field.into());
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause)
+        pub fn filter<E: Into<Expr<'a>>>(&mut self, expr: E) -> &mut Self {
+            self.push_condition(expr.into())
+        }
+
+        /// Filter result set based on conditions (`WHERE` clause), binding each `?` in `expr`
+        /// to the corresponding value
+        ///
+        /// Building the query returns an error if the number of `?` markers in `expr` is not equal
+        /// to the number of `values`.
+        pub fn filter_with<S: Into<Cow<'a, str>>>(
+            &mut self,
+            expr: S,
+            values: Vec<Value>,
+        ) -> &mut Self {
+            self.push_condition(Expr::Bound(expr.into(), values))
+        }
+
+        fn push_condition(&mut self, condition: Expr<'a>) -> &mut Self {
+            if self.conditions.is_none() {
+                self.conditions = Some(Vec::new());
+            }
+
+            match self.conditions {
+                Some(ref mut current_conditions) => {
+                    current_conditions.push(condition);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Apply a scope's conditions, as a single group
+        ///
+        /// A scope's ordering does not change which rows are affected, so it is ignored. `UPDATE`
+        /// queries cannot join other tables; building a query with a scope that has joins returns
+        /// an error.
+        pub fn scope<S: IntoScope<'a>>(&mut self, scope: S) -> &mut Self {
+            let scope = scope.into_scope();
+
+            if !scope.conditions.is_empty() {
+                self.push_condition(Expr::And(scope.conditions));
+            }
+
+            if !scope.joins.is_empty() {
+                if self.joins.is_none() {
+                    self.joins = Some(Vec::new());
+                }
+
+                match self.joins {
+                    Some(ref mut current_joins) => {
+                        current_joins.extend(scope.joins);
+                    }
+                    None => unreachable!(),
+                }
+            }
+
+            self
+        }
+
+        /// Add a named subquery that the query can refer to as a table (`WITH` clause)
+        ///
+        /// Data-modifying subqueries (`INSERT`, `UPDATE` and `DELETE`) are only supported by
+        /// PostgreSQL.
+        pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: None,
+                recursive: false,
+                query: query.into(),
+            })
+        }
+
+        /// Add a named subquery that can refer to itself, with the names of its columns
+        /// (`WITH RECURSIVE` clause)
+        pub fn with_recursive<N, Q>(&mut self, name: N, columns: &[&'a str], query: Q) -> &mut Self
+        where
+            N: Into<Cow<'a, str>>,
+            Q: Into<Statement<'a>>,
+        {
+            self.push_cte(Cte {
+                name: Ident::new(name),
+                columns: Some(columns.iter().map(|c| Ident::new(*c)).collect()),
+                recursive: true,
+                query: query.into(),
+            })
+        }
+
+        fn push_cte(&mut self, cte: Cte<'a>) -> &mut Self {
+            if self.ctes.is_none() {
+                self.ctes = Some(Vec::new());
+            }
+
+            match self.ctes {
+                Some(ref mut current_ctes) => {
+                    current_ctes.push(cte);
+                }
+                None => unreachable!(),
+            }
+
+            self
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for PostgreSQL
+        ///
+        /// # Panics
+        ///
+        /// Panics if the query is invalid or cannot be expressed in PostgreSQL; use `try_build`
+        /// to handle the error instead.
+        pub fn build(&self) -> (String, Vec<Value>) {
+            self.try_build().unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Generate SQL query (`String`) and its bound values, for PostgreSQL, or an error if
+        /// the query is invalid
+        pub fn try_build(&self) -> Result<(String, Vec<Value>), BuildError> {
+            self.build_for(Dialect::Postgres)
+        }
+
+        /// Generate SQL query (`String`) and its bound values from subsequent method calls,
+        /// for `dialect`
+        pub fn build_for(&self, dialect: Dialect) -> Result<(String, Vec<Value>), BuildError> {
+            let mut query = Writer::new(dialect);
+            self.write(&mut query)?;
+            query.finish()
+        }
+
+        /// Append the query to `query`, without a terminating `;`
+        fn write(&self, query: &mut Writer) -> Result<(), BuildError> {
+            if let Some(ref ctes) = self.ctes {
+                query.push_ctes(ctes)?;
+            }
+
+            query.push("UPDATE ");
+            query.push_ident(&self.table);
+
+            if self.values.0.is_empty() {
+                return Err(BuildError::MissingClause {
+                    clause: "UPDATE",
+                    requires: "SET",
+                });
+            }
+
+            query.push(" SET ");
+            query.push_assignments(&self.values)?;
+
+            if self.joins.is_some() {
+                return Err(BuildError::Incompatible {
+                    clause: "JOIN",
+                    with: "UPDATE",
+                });
+            }
+
+            if let Some(ref conditions) = self.conditions {
+                query.push(" WHERE ");
+                query.push_conditions(conditions)?;
+            }
+
+            if let Some(ref returns) = self.returns {
+                query.push_returning(returns)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Helper function to construct new `ALTER TABLE` query builder
+    pub fn alter_table<'a, T: Into<Ident<'a>>>(table: T) -> AlterTable<'a> {
+        AlterTable::new(table)
+    }
+
+    /// Helper function to construct new compound (`UNION`, `INTERSECT` or `EXCEPT`) query
+    /// builder, starting with the rows of `first`
+    pub fn compound(first: Select<'_>) -> Compound<'_> {
+        Compound::new(first)
+    }
+
+    /// Helper function to construct new `CREATE INDEX` query builder, for an index of `table`
+    /// named `name`
+    pub fn create_index<'a, N, T>(name: N, table: T) -> CreateIndex<'a>
+    where
+        N: Into<Ident<'a>>,
+        T: Into<Ident<'a>>,
+    {
+        CreateIndex::new(name, table)
+    }
+
+    /// Helper function to construct new `CREATE TABLE` query builder
+    pub fn create_table<'a, T: Into<Ident<'a>>>(table: T) -> CreateTable<'a> {
+        CreateTable::new(table)
+    }
+
+    /// Helper function to construct new `DELETE` query builder
+    pub fn delete<'a, T: Into<Ident<'a>>>(table: T) -> Delete<'a> {
+        Delete::new(table)
+    }
+
+    /// Helper function to construct new `DROP INDEX` query builder
+    pub fn drop_index<'a, N: Into<Ident<'a>>>(index: N) -> DropObject<'a> {
+        DropObject::index(index)
+    }
+
+    /// Helper function to construct new `DROP TABLE` query builder
+    pub fn drop_table<'a, T: Into<Ident<'a>>>(table: T) -> DropObject<'a> {
+        DropObject::table(table)
+    }
+
+    /// Helper function to construct new `DROP VIEW` query builder
+    pub fn drop_view<'a, V: Into<Ident<'a>>>(view: V) -> DropObject<'a> {
+        DropObject::view(view)
+    }
+
+    /// Helper function to construct new `INSERT` query builder
+    pub fn insert<'a, T: Into<Ident<'a>>>(table: T) -> Insert<'a> {
+        Insert::new(table)
+    }
+
+    /// Helper function to construct new `SELECT` query builder
+    pub fn select<'a, T: Into<Table<'a>>>(table: T) -> Select<'a> {
+        Select::new(table)
+    }
+
+    /// Helper function to construct new `UPDATE` query builder
+    pub fn update<'a, T: Into<Ident<'a>>>(table: T) -> Update<'a> {
+        Update::new(table)
+    }
+
+    /// Helper function to construct a lock on rows for updating them (`FOR UPDATE`)
+    pub fn for_update<'a>() -> Lock<'a> {
+        Lock::new(LockStrength::Update)
+    }
+
+    /// Helper function to construct a lock on rows that only prevents other transactions from
+    /// updating them (`FOR SHARE`)
+    pub fn for_share<'a>() -> Lock<'a> {
+        Lock::new(LockStrength::Share)
+    }
+
+    /// Helper function to construct an ascending sort key for an `ORDER` clause
+    pub fn asc<'a, E: Into<Expr<'a>>>(expr: E) -> OrderBy<'a> {
+        OrderBy::new(expr, Order::Asc)
+    }
+
+    /// Helper function to construct a descending sort key for an `ORDER` clause
+    pub fn desc<'a, E: Into<Expr<'a>>>(expr: E) -> OrderBy<'a> {
+        OrderBy::new(expr, Order::Desc)
+    }
+
+    /// Helper function to construct a column expression (e.g., `users.id`)
+    pub fn col<'a, I: Into<Ident<'a>>>(name: I) -> Expr<'a> {
+        Expr::Column(name.into())
+    }
+
+    /// Helper function to construct a value expression, bound as a query parameter
+    pub fn val<'a, V: Into<Value>>(value: V) -> Expr<'a> {
+        Expr::Value(value.into())
+    }
+
+    /// Helper function to construct an expression written verbatim
+    pub fn raw<'a, S: Into<Cow<'a, str>>>(sql: S) -> Expr<'a> {
+        Expr::Raw(sql.into())
+    }
+
+    /// Helper function to construct an expression that holds if all `exprs` hold (`AND`)
+    pub fn and<'a, I, E>(exprs: I) -> Expr<'a>
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Expr<'a>>,
+    {
+        Expr::And(exprs.into_iter().map(Into::into).collect())
+    }
+
+    /// Helper function to construct an expression that holds if any of `exprs` hold (`OR`)
+    pub fn or<'a, I, E>(exprs: I) -> Expr<'a>
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Expr<'a>>,
+    {
+        Expr::Or(exprs.into_iter().map(Into::into).collect())
+    }
+
+    /// Helper function to construct the negation of an expression (`NOT`)
+    pub fn not<'a, E: Into<Expr<'a>>>(expr: E) -> Expr<'a> {
+        Expr::Not(Box::new(expr.into()))
+    }
+
+    /// Helper function to construct an expression for the value a conflicting `INSERT` proposed
+    /// for a column (`EXCLUDED.column` or `VALUES(column)`)
+    pub fn excluded<'a, I: Into<Ident<'a>>>(column: I) -> Expr<'a> {
+        Expr::Excluded(column.into())
+    }
+
+    /// Helper function to construct a new window of all rows, for a window function (`OVER`)
+    pub fn window<'a>() -> Window<'a> {
+        Window::new()
+    }
+
+    /// Helper function to construct a new column definition
+    pub fn column<'a, N: Into<Ident<'a>>>(name: N, data_type: DataType<'a>) -> Column<'a> {
+        Column::new(name, data_type)
+    }
+
+    /// Helper function to construct a reference to columns of a table, for a foreign key
+    pub fn references<'a, T: Into<Ident<'a>>>(table: T, columns: &[&'a str]) -> References<'a> {
+        References::new(table, columns)
+    }
+
+    /// Helper function to construct a new, empty scope
+    pub fn scope<'a>() -> Scope<'a> {
+        Scope::new()
+    }
+
+    /// Helper function to construct a subquery usable as a table, with an alias (`AS`)
+    pub fn derived<'a, S: Into<Cow<'a, str>>>(query: Select<'a>, alias: S) -> Table<'a> {
+        Table::Derived(Box::new(query), Ident::new(alias))
+    }
+
+    /// Helper function to construct a subquery usable as a table that can refer to the tables
+    /// before it in the `FROM` clause, with an alias (`LATERAL`)
+    pub fn lateral<'a, S: Into<Cow<'a, str>>>(query: Select<'a>, alias: S) -> Table<'a> {
+        Table::Lateral(Box::new(query), Ident::new(alias))
+    }
+
+    /// Helper function to construct a subquery that returns a single value
+    pub fn subquery(query: Select<'_>) -> Expr<'_> {
+        Expr::Subquery(Box::new(query))
+    }
+
+    /// Helper function to construct an expression that holds if a subquery returns any rows
+    /// (`EXISTS`)
+    pub fn exists(query: Select<'_>) -> Expr<'_> {
+        Expr::Exists(Box::new(query))
+    }
+
+    /// A value of column `C`'s type, bound as a query parameter
+    fn typed_value<'a, C: TypedColumn, V: Into<C::Type>>(value: V) -> Expr<'a> {
+        Expr::Value(value.into().into())
+    }
+}
+
+/// Declare a table and its columns, with the Rust type of each column's values
+///
+/// `table! { users { id: i64, name: String } }` declares a module `users`, with a `table` type
+/// and a type for each column, which query builders accept in place of names (qualified by the
+/// table's name, except where a bare column name is required, or by an alias with `of`). Using
+/// a column that is not declared fails to compile, and so does comparing a column to (or setting
+/// it to) a value of another type. Comparisons are methods of `query_builder::TypedColumn`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate pinto;
+///
+/// use pinto::query_builder::{self, TypedColumn};
+///
+/// table! {
+///     users {
+///         id: i64,
+///         name: String,
+///     }
+/// }
+///
+/// fn main() {
+///     let (query, _) = query_builder::update(users::table)
+///         .set(users::name, "Jacob")
+///         .filter(users::id.eq(1))
+///         .build();
+///     assert_eq!("UPDATE users SET name = $1 WHERE users.id = $2;", query);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate pinto;
+///
+/// use pinto::query_builder::{self, TypedColumn};
+///
+/// table! {
+///     users {
+///         id: i64,
+///         name: String,
+///     }
+/// }
+///
+/// fn main() {
+///     // `id` is an `i64` column
+///     query_builder::select(users::table).filter(users::id.eq("1"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! table {
+    ($(#[$attr:meta])* $table:ident { $($column:ident : $type:ty),+ $(,)* }) => {
+        $(#[$attr])*
+        #[allow(non_camel_case_types, dead_code)]
+        pub mod $table {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// The table
+            #[derive(Debug, Clone, Copy, Default)]
+            pub struct table;
+
+            impl $crate::query_builder::TypedTable for table {
+                const NAME: &'static str = stringify!($table);
+            }
+
+            impl<'a> From<table> for $crate::query_builder::Ident<'a> {
+                fn from(_: table) -> Self {
+                    $crate::query_builder::Ident::new(stringify!($table))
+                }
+            }
+
+            impl<'a> From<table> for $crate::query_builder::Table<'a> {
+                fn from(_: table) -> Self {
+                    $crate::query_builder::Ident::new(stringify!($table)).into()
+                }
+            }
+
+            $(
+                #[derive(Debug, Clone, Copy, Default)]
+                pub struct $column;
+
+                impl $crate::query_builder::TypedColumn for $column {
+                    type Table = table;
+                    type Type = $type;
+                    const NAME: &'static str = stringify!($column);
+                }
+            )+
+        }
+    };
+}
+
+/// A module to apply versioned schema changes (migrations) to a database.
+pub mod migrations {
+    use std::borrow::Cow;
+    use std::convert::Infallible;
+    use std::error::Error;
+    use std::fmt;
+
+    use query_builder::{self, col, column, raw, BuildError, DataType, Dialect, Ident, Order};
+    use query_builder::{CreateTable, Select, Statement};
+
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    /// A versioned schema change, with the statements that apply and revert it
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Migration<'a> {
+        version: i64,
+        name: Cow<'a, str>,
+        up: Vec<Statement<'a>>,
+        down: Option<Vec<Statement<'a>>>,
+        transaction: bool,
+    }
+
+    /// A set of migrations, applied in order of version and tracked in a schema table
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Migrations<'a> {
+        table: Ident<'a>,
+        migrations: Vec<Migration<'a>>,
+    }
+
+    /// A database connection that migrations can be run on (e.g., an in-memory SQLite database
+    /// in tests)
+    pub trait Executor {
+        /// The error returned by the database
+        type Error;
+
+        /// The dialect that statements are generated for
+        fn dialect(&self) -> Dialect;
+
+        /// Execute a statement that does not return rows
+        fn execute(&mut self, sql: &str) -> Result<(), Self::Error>;
+
+        /// Execute a query that returns versions, one per row
+        fn query_versions(&mut self, sql: &str) -> Result<Vec<i64>, Self::Error>;
+    }
+
+    /// An error encountered while planning or running migrations
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum MigrationError<E = Infallible> {
+        /// A statement of a migration (or of the schema table, if `version` is `None`) could
+        /// not be generated
+        Build {
+            version: Option<i64>,
+            error: BuildError,
+        },
+        /// The database returned an error while applying (or reverting) a migration (or
+        /// creating or querying the schema table, if `version` is `None`)
+        Execute { version: Option<i64>, error: E },
+        /// More than one migration has the same version
+        DuplicateVersion(i64),
+        /// The schema table records a version that no migration has
+        UnknownVersion(i64),
+        /// A migration that has to be reverted has no `down` statements
+        Irreversible(i64),
+        /// A migration that runs in a transaction has a statement that cannot (e.g.,
+        /// `CREATE INDEX CONCURRENTLY`)
+        NotTransactional(i64),
+    }
+
+    impl<'a> Migration<'a> {
+        /// Construct a new migration, without any statements
+        pub fn new<N: Into<Cow<'a, str>>>(version: i64, name: N) -> Self {
+            Migration {
+                version,
+                name: name.into(),
+                up: Vec::new(),
+                down: None,
+                transaction: true,
+            }
+        }
+
+        /// Add a statement that applies the migration (a query builder, or SQL written verbatim,
+        /// without a terminating `;`)
+        pub fn up<S: Into<Statement<'a>>>(mut self, statement: S) -> Self {
+            self.up.push(statement.into());
+            self
+        }
+
+        /// Add a statement that reverts the migration; statements run in the order they are
+        /// added
+        pub fn down<S: Into<Statement<'a>>>(mut self, statement: S) -> Self {
+            self.down
+                .get_or_insert_with(Vec::new)
+                .push(statement.into());
+            self
+        }
+
+        /// Apply (and revert) the migration outside of a transaction, which statements such as
+        /// `CREATE INDEX CONCURRENTLY` require
+        ///
+        /// If a statement fails, the statements before it are not rolled back.
+        pub fn without_transaction(mut self) -> Self {
+            self.transaction = false;
+            self
+        }
+
+        /// Whether the migration is applied (and reverted) in a transaction on `dialect`
+        fn in_transaction(&self, dialect: Dialect) -> bool {
+            // MySQL commits the transaction before most schema changes
+            self.transaction && dialect != Dialect::MySql
+        }
+
+        /// The migration's version
+        pub fn version(&self) -> i64 {
+            self.version
+        }
+
+        /// The migration's name
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    impl<'a> Default for Migrations<'a> {
+        fn default() -> Self {
+            Migrations::new()
+        }
+    }
+
+    impl<'a> Migrations<'a> {
+        /// Construct a new, empty set of migrations, tracked in the `schema_migrations` table
+        pub fn new() -> Self {
+            Migrations {
+                table: Ident::new("schema_migrations"),
+                migrations: Vec::new(),
+            }
+        }
+
+        /// Set the table that applied versions are recorded in
+        pub fn table<T: Into<Ident<'a>>>(&mut self, table: T) -> &mut Self {
+            self.table = table.into();
+            self
+        }
+
+        /// Add a migration
+        pub fn add(&mut self, migration: Migration<'a>) -> &mut Self {
+            self.migrations.push(migration);
+            self
+        }
+
+        /// The `CREATE TABLE` query for the schema table, which does nothing if it exists
+        pub fn schema_table(&self) -> CreateTable<'a> {
+            let mut query = query_builder::create_table(self.table.clone());
+            query
+                .if_not_exists()
+                .column(column("version", DataType::BigInt).not_null().primary_key())
+                .column(column("name", DataType::Text).not_null())
+                .column(
+                    column("applied_at", DataType::Timestamp)
+                        .not_null()
+                        .default(raw("CURRENT_TIMESTAMP")),
+                );
+            query
+        }
+
+        /// The `SELECT` query for the applied versions, in ascending order
+        pub fn versions_query(&self) -> Select<'a> {
+            let mut query = query_builder::select(self.table.clone());
+            query.fields(&["version"]).order_by("version", Order::Asc);
+            query
+        }
+
+        /// The migrations that have not been applied, in order of version
+        pub fn pending(&self, applied: &[i64]) -> Result<Vec<&Migration<'a>>, MigrationError> {
+            let migrations = self.sorted()?;
+            if let Some(&version) = applied
+                .iter()
+                .find(|v| !migrations.iter().any(|m| m.version == **v))
+            {
+                return Err(MigrationError::UnknownVersion(version));
+            }
+
+            Ok(migrations
+                .into_iter()
+                .filter(|m| !applied.contains(&m.version))
+                .collect())
+        }
+
+        /// The applied migrations with a version greater than `target`, in the order they are
+        /// reverted (descending version)
+        pub fn reverting(
+            &self,
+            applied: &[i64],
+            target: i64,
+        ) -> Result<Vec<&Migration<'a>>, MigrationError> {
+            // Check that every applied version has a migration
+            self.pending(applied)?;
+
+            let mut migrations: Vec<_> = self
+                .sorted()?
+                .into_iter()
+                .filter(|m| m.version > target && applied.contains(&m.version))
+                .collect();
+            migrations.reverse();
+
+            if let Some(migration) = migrations.iter().find(|m| m.down.is_none()) {
+                return Err(MigrationError::Irreversible(migration.version));
+            }
+
+            Ok(migrations)
+        }
+
+        /// Generate the script (`String`) that creates the schema table and applies the pending
+        /// migrations, for `dialect`
+        pub fn up_script(
+            &self,
+            dialect: Dialect,
+            applied: &[i64],
+        ) -> Result<String, MigrationError> {
+            let mut script = vec![self.schema_table_sql(dialect)?];
+            for migration in self.pending(applied)? {
+                script.extend(self.up_sql(migration, dialect)?);
+            }
+            Ok(script.join("\n"))
+        }
+
+        /// Generate the script (`String`) that reverts the applied migrations with a version
+        /// greater than `target`, for `dialect`
+        pub fn down_script(
+            &self,
+            dialect: Dialect,
+            applied: &[i64],
+            target: i64,
+        ) -> Result<String, MigrationError> {
+            let mut script = Vec::new();
+            for migration in self.reverting(applied, target)? {
+                script.extend(self.down_sql(migration, dialect)?);
+            }
+            Ok(script.join("\n"))
+        }
+
+        /// Create the schema table if needed, and apply the pending migrations, returning their
+        /// versions
+        ///
+        /// On PostgreSQL and SQLite, each migration is applied in a transaction, unless it is
+        /// `without_transaction`.
+        pub fn run<E: Executor>(
+            &self,
+            executor: &mut E,
+        ) -> Result<Vec<i64>, MigrationError<E::Error>> {
+            let dialect = executor.dialect();
+            let applied = self.applied(executor)?;
+
+            let mut versions = Vec::new();
+            for migration in self.pending(&applied).map_err(MigrationError::cast)? {
+                let statements = self
+                    .up_sql(migration, dialect)
+                    .map_err(MigrationError::cast)?;
+                let transactional = migration.in_transaction(dialect);
+                execute(executor, migration.version, transactional, &statements)?;
+                versions.push(migration.version);
+            }
+            Ok(versions)
+        }
+
+        /// Revert the applied migrations with a version greater than `target`, returning their
+        /// versions
+        ///
+        /// On PostgreSQL and SQLite, each migration is reverted in a transaction, unless it is
+        /// `without_transaction`.
+        pub fn rollback<E: Executor>(
+            &self,
+            executor: &mut E,
+            target: i64,
+        ) -> Result<Vec<i64>, MigrationError<E::Error>> {
+            let dialect = executor.dialect();
+            let applied = self.applied(executor)?;
+
+            let mut versions = Vec::new();
+            for migration in self
+                .reverting(&applied, target)
+                .map_err(MigrationError::cast)?
+            {
+                let statements = self
+                    .down_sql(migration, dialect)
+                    .map_err(MigrationError::cast)?;
+                let transactional = migration.in_transaction(dialect);
+                execute(executor, migration.version, transactional, &statements)?;
+                versions.push(migration.version);
+            }
+            Ok(versions)
+        }
+
+        /// The migrations in order of version
+        fn sorted(&self) -> Result<Vec<&Migration<'a>>, MigrationError> {
+            let mut migrations: Vec<_> = self.migrations.iter().collect();
+            migrations.sort_by_key(|m| m.version);
+
+            for pair in migrations.windows(2) {
+                if pair[0].version == pair[1].version {
+                    return Err(MigrationError::DuplicateVersion(pair[0].version));
+                }
+            }
+
+            Ok(migrations)
+        }
+
+        /// Create the schema table if needed, and query the applied versions
+        fn applied<E: Executor>(
+            &self,
+            executor: &mut E,
+        ) -> Result<Vec<i64>, MigrationError<E::Error>> {
+            let dialect = executor.dialect();
+            let create = self
+                .schema_table_sql(dialect)
+                .map_err(MigrationError::cast)?;
+            let select = Statement::from(self.versions_query())
+                .build_literal(dialect)
+                .map_err(|error| MigrationError::Build {
+                    version: None,
+                    error,
+                })?;
+
+            let execute_error = |error| MigrationError::Execute {
+                version: None,
+                error,
+            };
+            executor.execute(&create).map_err(execute_error)?;
+            executor.query_versions(&select).map_err(execute_error)
+        }
+
+        fn schema_table_sql(&self, dialect: Dialect) -> Result<String, MigrationError> {
+            Statement::from(self.schema_table())
+                .build_literal(dialect)
+                .map_err(|error| MigrationError::Build {
+                    version: None,
+                    error,
+                })
+        }
+
+        /// The statements that apply `migration` and record its version
+        fn up_sql(
+            &self,
+            migration: &Migration<'a>,
+            dialect: Dialect,
+        ) -> Result<Vec<String>, MigrationError> {
+            let mut record = query_builder::insert(self.table.clone());
+            record
+                .set("version", migration.version)
+                .set("name", migration.name.as_ref());
+
+            let mut statements: Vec<Statement> = migration.up.clone();
+            statements.push(record.into());
+            transaction(migration, &statements, dialect)
+        }
+
+        /// The statements that revert `migration` and remove the record of its version
+        fn down_sql(
+            &self,
+            migration: &Migration<'a>,
+            dialect: Dialect,
+        ) -> Result<Vec<String>, MigrationError> {
+            let mut record = query_builder::delete(self.table.clone());
+            record.filter(col("version").eq(migration.version));
+
+            let mut statements: Vec<Statement> = match migration.down {
+                Some(ref down) => down.clone(),
+                None => return Err(MigrationError::Irreversible(migration.version)),
+            };
+            statements.push(record.into());
+            transaction(migration, &statements, dialect)
+        }
+    }
+
+    impl<E> MigrationError<E> {
+        /// Convert an error that cannot come from an executor
+        fn cast(error: MigrationError) -> Self {
+            match error {
+                MigrationError::Build { version, error } => {
+                    MigrationError::Build { version, error }
+                }
+                MigrationError::Execute { error, .. } => match error {},
+                MigrationError::DuplicateVersion(v) => MigrationError::DuplicateVersion(v),
+                MigrationError::UnknownVersion(v) => MigrationError::UnknownVersion(v),
+                MigrationError::Irreversible(v) => MigrationError::Irreversible(v),
+                MigrationError::NotTransactional(v) => MigrationError::NotTransactional(v),
+            }
+        }
+    }
+
+    impl<E: fmt::Display> fmt::Display for MigrationError<E> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                MigrationError::Build {
+                    version: Some(version),
+                    ref error,
+                } => write!(f, "migration {}: {}", version, error),
+                MigrationError::Build {
+                    version: None,
+                    ref error,
+                } => write!(f, "schema table: {}", error),
+                MigrationError::Execute {
+                    version: Some(version),
+                    ref error,
+                } => write!(f, "migration {}: {}", version, error),
+                MigrationError::Execute {
+                    version: None,
+                    ref error,
+                } => write!(f, "schema table: {}", error),
+                MigrationError::DuplicateVersion(version) => {
+                    write!(f, "more than one migration has version {}", version)
+                }
+                MigrationError::UnknownVersion(version) => {
+                    write!(
+                        f,
+                        "version {} was applied, but no migration has it",
+                        version
+                    )
+                }
+                MigrationError::Irreversible(version) => {
+                    write!(f, "migration {} cannot be reverted", version)
+                }
+                MigrationError::NotTransactional(version) => write!(
+                    f,
+                    "migration {} has a statement that cannot run in a transaction",
+                    version
+                ),
+            }
+        }
+    }
+
+    impl<E: Error> Error for MigrationError<E> {}
+
+    /// Generate `statements` of `migration`, in a transaction unless it opts out or the dialect
+    /// cannot roll back schema changes
+    fn transaction(
+        migration: &Migration,
+        statements: &[Statement],
+        dialect: Dialect,
+    ) -> Result<Vec<String>, MigrationError> {
+        let version = migration.version;
+        let transactional = migration.in_transaction(dialect);
+        if transactional && !statements.iter().all(Statement::is_transactional) {
+            return Err(MigrationError::NotTransactional(version));
+        }
+
+        let mut sql = Vec::with_capacity(statements.len() + 2);
+        if transactional {
+            sql.push(String::from("BEGIN;"));
+        }
+        for statement in statements {
+            let statement =
+                statement
+                    .build_literal(dialect)
+                    .map_err(|error| MigrationError::Build {
+                        version: Some(version),
+                        error,
+                    })?;
+            sql.push(statement);
+        }
+        if transactional {
+            sql.push(String::from("COMMIT;"));
+        }
+        Ok(sql)
+    }
+
+    /// Execute the statements of a migration, rolling back its transaction (if `transactional`)
+    /// if one fails
+    fn execute<E: Executor>(
+        executor: &mut E,
+        version: i64,
+        transactional: bool,
+        statements: &[String],
+    ) -> Result<(), MigrationError<E::Error>> {
+        for statement in statements {
+            if let Err(error) = executor.execute(statement) {
+                if transactional {
+                    // The original error is more useful than one from the rollback
+                    let _ = executor.execute("ROLLBACK;");
+                }
+                return Err(MigrationError::Execute {
+                    version: Some(version),
+                    error,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+extern crate rusqlite;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+#[cfg(test)]
+mod tests {
+    use super::migrations::{Executor, Migration, MigrationError, Migrations};
+    use super::query_builder;
+    use super::query_builder::TypedColumn;
+    use super::query_builder::{alter_table, drop_index, drop_table, drop_view};
+    use super::query_builder::{and, asc, col, desc, excluded, not, or, raw, val};
+    use super::query_builder::{column, create_index, references};
+    use super::query_builder::{compound, derived, exists, lateral, scope, subquery, window};
+    use super::query_builder::{for_share, for_update};
+    use super::query_builder::{BuildError, Dialect, Expr, Ident, IntoScope, Row, Scope, Value};
+    use super::query_builder::{DataType, IndexMethod, ReferentialAction};
+    use super::query_builder::{
+        FrameBound, Join, Lock, LockStrength, Nulls, Order, OrderBy, Window,
+    };
+
+    #[test]
+    fn test_debug() {
+        let query_builder = query_builder::select("users");
+        let query = format!("{:?}", query_builder);
+        assert_eq!(
+            "Select { table: Named(\"users\"), aliases: None, fields: None, distinct: None, order: None, \
+             joins: None, groupings: None, havings: None, windows: None, conditions: None, limit: 0, offset: 0, \
+             locks: None, ctes: None }",
+            query
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let query_builder = query_builder::select("users");
+        let query = format!("{}", query_builder);
+        assert_eq!("SELECT * FROM users;", query);
+
+        let query = format!("{}", query_builder::update("users"));
+        assert_eq!("<invalid query: UPDATE requires SET>", query);
+    }
+
+    #[test]
+    fn test_delete_query() {
+        let (query, _) = query_builder::delete("users").build();
+        assert_eq!("DELETE FROM users;", query);
+    }
+
+    #[test]
+    fn test_delete_query_with_conditions() {
+        let (query, _) = query_builder::delete("users")
+            .filter("name = $1")
+            .filter("karma <= $2")
+            .build();
+        assert_eq!(
+            "DELETE FROM users WHERE (name = $1) AND (karma <= $2);",
+            query
+        );
+    }
+
+    #[test]
+    fn test_insert_query() {
+        let (query, params) = query_builder::insert("users")
+            .set("name", "Jacob")
+            .set("karma", 0)
+            .build();
+        assert_eq!("INSERT INTO users (name, karma) VALUES ($1, $2);", query);
+        assert_eq!(vec![Value::from("Jacob"), Value::Int(0)], params);
+    }
+
+    #[test]
+    fn test_insert_query_with_reordered_fields() {
+        let (query, params) = query_builder::insert("users")
+            .set("name", "Jacob")
+            .set("karma", 0)
+            .set("email", "jacob@example.com")
+            .set("name", "Jacob Budin")
+            .unset("karma")
+            .reorder(&["email"])
+            .build();
+        assert_eq!("INSERT INTO users (email, name) VALUES ($1, $2);", query);
+        assert_eq!(
+            vec![Value::from("jacob@example.com"), Value::from("Jacob Budin")],
+            params
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_raw_value() {
+        let (query, params) = query_builder::insert("users")
+            .set_raw("created_at", "NOW()")
+            .returning("id")
+            .build();
+        assert_eq!(
+            "INSERT INTO users (created_at) VALUES (NOW()) RETURNING id;",
+            query
+        );
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_select_query() {
+        let (query, _) = query_builder::select("users").build();
+        assert_eq!("SELECT * FROM users;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_fields() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .build();
+        assert_eq!("SELECT id, name FROM users;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_alias() {
+        let (query, _) = query_builder::select("users")
+            .alias("users", "u")
+            .fields(&["id", "name"])
+            .build();
+        assert_eq!("SELECT id, name FROM users AS u;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_limit() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .limit(15)
+            .build();
+        assert_eq!("SELECT id, name FROM users LIMIT 15;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_offset() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .limit(15)
+            .offset(30)
+            .build();
+        assert_eq!("SELECT id, name FROM users LIMIT 15 OFFSET 30;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_group() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name", "MAX(karma) AS max"])
+            .group_by("name")
+            .having("max > 100")
+            .build();
+        assert_eq!(
+            "SELECT id, name, MAX(karma) AS max FROM users GROUP BY name HAVING max > 100;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_conditions() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .filter("id = $1")
+            .filter("name = $2")
+            .build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE (id = $1) AND (name = $2);",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_bound_conditions() {
+        let (query, params) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .filter_with(
+                "karma BETWEEN ? AND ?",
+                vec![Value::Int(10), Value::Int(20)],
+            )
+            .filter_with("deleted = ?", vec![Value::Bool(false)])
+            .group_by("name")
+            .having_with("COUNT(*) > ?", vec![Value::Int(1)])
+            .build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE (karma BETWEEN $1 AND $2) AND (deleted = $3) \
+             GROUP BY name HAVING COUNT(*) > $4;",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::Int(10),
+                Value::Int(20),
+                Value::Bool(false),
+                Value::Int(1),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_mismatched_bound_conditions() {
+        assert_eq!(
+            Err(BuildError::BoundValueMismatch {
+                markers: 2,
+                values: 1,
+            }),
+            query_builder::select("users")
+                .filter_with("id = ? OR id = ?", vec![Value::Int(1)])
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::BoundValueMismatch {
+                markers: 1,
+                values: 0,
+            }),
+            query_builder::select("users")
+                .group_by("name")
+                .having_with("COUNT(*) > ?", vec![])
+                .try_build()
+        );
+    }
+
+    #[test]
+    fn test_bound_conditions_with_quoted_markers() {
+        let (query, params) = query_builder::select("users")
+            .filter_with(
+                "name = '?' AND \"who?\" = ? AND id = ?",
+                vec![Value::Int(1), Value::Int(2)],
+            )
+            .build();
+        assert_eq!(
+            "SELECT * FROM users WHERE name = '?' AND \"who?\" = $1 AND id = $2;",
+            query
+        );
+        assert_eq!(vec![Value::Int(1), Value::Int(2)], params);
+
+        let condition = Expr::Bound("id = ? OR id = ?".into(), vec![Value::Int(1)]);
+        assert_eq!(
+            Err(BuildError::BoundValueMismatch {
+                markers: 2,
+                values: 1,
+            }),
+            query_builder::select("users").filter(condition).try_build()
+        );
+        let condition = Expr::Bound("id = ?".into(), vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(
+            Err(BuildError::BoundValueMismatch {
+                markers: 1,
+                values: 2,
+            }),
+            query_builder::delete("users").filter(condition).try_build()
+        );
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(Value::Int(7), Value::from(7u8));
+        assert_eq!(Value::Float(0.5), Value::from(0.5f32));
+        assert_eq!(Value::Bytes(vec![1, 2]), Value::from(&[1u8, 2][..]));
+        assert_eq!(Value::Null, Value::from(None::<i32>));
+        assert_eq!(Value::Text("a".to_string()), Value::from(Some("a")));
+    }
+
+    #[test]
+    fn test_select_query_with_order() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .filter("name = $1")
+            .order_by("id", query_builder::Order::Asc)
+            .build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE name = $1 ORDER BY id ASC;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_join() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .filter("name = $1")
+            .alias("posts", "p")
+            .join("posts", "p.user_id", "users.id", query_builder::Join::Left)
+            .build();
+        assert_eq!("SELECT id, name FROM users LEFT JOIN posts AS p ON p.user_id = users.id WHERE name = $1;", query);
+    }
+
+    #[test]
+    fn test_update_query() {
+        let (query, _) = query_builder::update("users")
+            .set_raw("karma", "0")
+            .set_raw("last_login", "'1970-01-01'")
+            .build();
+        assert_eq!(
+            "UPDATE users SET karma = 0, last_login = '1970-01-01';",
+            query
+        );
+    }
+
+    #[test]
+    fn test_update_query_with_conditions() {
+        let (query, params) = query_builder::update("users")
+            .set("karma", 0)
+            .filter_with("name = ?", vec![Value::from("Jacob")])
+            .filter_with("last_login < ?", vec![Value::from("1970-01-01")])
+            .build();
+        assert_eq!(
+            "UPDATE users SET karma = $1 WHERE (name = $2) AND (last_login < $3);",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::Int(0),
+                Value::from("Jacob"),
+                Value::from("1970-01-01"),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_dialect_placeholders() {
+        let mut builder = query_builder::select("users");
+        builder.filter_with(
+            "id = ? OR name = ?",
+            vec![Value::Int(1), Value::from("Jacob")],
+        );
+        let expected = [
+            (
+                Dialect::Postgres,
+                "SELECT * FROM users WHERE id = $1 OR name = $2;",
+            ),
+            (
+                Dialect::MySql,
+                "SELECT * FROM users WHERE id = ? OR name = ?;",
+            ),
+            (
+                Dialect::Sqlite,
+                "SELECT * FROM users WHERE id = ? OR name = ?;",
+            ),
+        ];
+        for &(dialect, sql) in expected.iter() {
+            let (query, params) = builder.build_for(dialect).unwrap();
+            assert_eq!(sql, query);
+            assert_eq!(vec![Value::Int(1), Value::from("Jacob")], params);
+        }
+    }
+
+    #[test]
+    fn test_dialect_offset_without_limit() {
+        let mut builder = query_builder::select("users");
+        builder.offset(30);
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!("SELECT * FROM users OFFSET 30;", query);
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "SELECT * FROM users LIMIT 18446744073709551615 OFFSET 30;",
+            query
+        );
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!("SELECT * FROM users LIMIT -1 OFFSET 30;", query);
+    }
+
+    #[test]
+    fn test_dialect_returning() {
+        let mut builder = query_builder::update("users");
+        builder.set("karma", 0).returning("id");
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!("UPDATE users SET karma = ? RETURNING id;", query);
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "RETURNING",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+    }
+
+    #[test]
+    fn test_dialect_quote() {
+        assert_eq!("\"order\"", Dialect::Postgres.quote("order"));
+        assert_eq!("`or``der`", Dialect::MySql.quote("or`der"));
+        assert_eq!("\"a\"\"b\"", Dialect::Sqlite.quote("a\"b"));
+    }
+
+    #[test]
+    fn test_identifier_quoting() {
+        let mut builder = query_builder::select("public.user");
+        builder
+            .fields(&["id", "user.order", "COUNT(*) AS total"])
+            .alias("public.user", "select")
+            .join(
+                "order",
+                "order.user_id",
+                "user.id",
+                query_builder::Join::Inner,
+            )
+            .group_by("user.order");
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "SELECT id, \"user\".\"order\", COUNT(*) AS total FROM public.\"user\" AS \"select\" \
+             INNER JOIN \"order\" ON \"order\".user_id = \"user\".id GROUP BY \"user\".\"order\";",
+            query
+        );
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "SELECT id, `user`.`order`, COUNT(*) AS total FROM public.`user` AS `select` \
+             INNER JOIN `order` ON `order`.user_id = `user`.id GROUP BY `user`.`order`;",
+            query
+        );
+
+        let mut builder = query_builder::select("users");
+        builder.fields(&["returning", "range", "UserId", "action"]);
+        assert_eq!(
+            "SELECT \"returning\", range, \"UserId\", action FROM users;",
+            builder.build().0
+        );
+        assert_eq!(
+            "SELECT returning, `range`, UserId, action FROM users;",
+            builder.build_for(Dialect::MySql).unwrap().0
+        );
+        assert_eq!(
+            "SELECT \"returning\", \"range\", UserId, \"action\" FROM users;",
+            builder.build_for(Dialect::Sqlite).unwrap().0
+        );
+
+        let mut builder = query_builder::select("users");
+        builder.filter("active").filter("true");
+        assert_eq!(
+            "SELECT * FROM users WHERE active AND (true);",
+            builder.build_for(Dialect::MySql).unwrap().0
+        );
+        let (query, _) = query_builder::select("users")
+            .filter("active")
+            .filter("NULL")
+            .build();
+        assert_eq!("SELECT * FROM users WHERE active AND (NULL);", query);
+        let (query, _) = query_builder::update("users")
+            .set("seen_at", raw("CURRENT_TIMESTAMP"))
+            .filter("FALSE")
+            .build();
+        assert_eq!(
+            "UPDATE users SET seen_at = CURRENT_TIMESTAMP WHERE FALSE;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_identifier_escaping() {
+        let (query, _) = query_builder::insert(Ident::new("odd.\"table\""))
+            .set(Ident::new("first name"), "Jacob")
+            .returning("t.*")
+            .build();
+        assert_eq!(
+            "INSERT INTO \"odd.\"\"table\"\"\" (\"first name\") VALUES ($1) RETURNING t.*;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_expressions() {
+        let (query, params) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .filter(or(vec![
+                col("name").like("J%"),
+                col("karma").between(10, 20).and(col("banned_at").is_null()),
+            ]))
+            .filter(not(col("role").is_in(vec!["admin", "owner"])))
+            .filter("deleted_at IS NULL")
+            .build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE (name LIKE $1 OR karma BETWEEN $2 AND $3 \
+             AND banned_at IS NULL) AND NOT (role IN ($4, $5)) AND (deleted_at IS NULL);",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::from("J%"),
+                Value::Int(10),
+                Value::Int(20),
+                Value::from("admin"),
+                Value::from("owner"),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_expression_parenthesization() {
+        let (query, _) = query_builder::delete("users")
+            .filter(
+                and(vec![col("a").eq(1).or(col("b").ne(2)), raw("c > 3")])
+                    .or(col("d").is_not_null()),
+            )
+            .build();
+        assert_eq!(
+            "DELETE FROM users WHERE (a = $1 OR b <> $2) AND (c > 3) OR d IS NOT NULL;",
+            query
+        );
+
+        let (query, _) = query_builder::update("users")
+            .set("karma", val(0))
+            .filter(col("karma").gt(col("max_karma")))
+            .filter(col("tags").is_in(Vec::<Value>::new()))
+            .filter(or(Vec::<&str>::new()))
+            .build();
+        assert_eq!(
+            "UPDATE users SET karma = $1 WHERE karma > max_karma AND 1 = 0 AND 1 = 0;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_raw_conditions_are_parenthesized() {
+        let (query, params) = query_builder::select("t")
+            .filter_with("a = ? OR b = ?", vec![Value::Int(1), Value::Int(2)])
+            .filter(col("tenant").eq(7))
+            .build();
+        assert_eq!(
+            "SELECT * FROM t WHERE (a = $1 OR b = $2) AND tenant = $3;",
+            query
+        );
+        assert_eq!(vec![Value::Int(1), Value::Int(2), Value::Int(7)], params);
+
+        let (query, _) = query_builder::delete("t")
+            .filter(raw("a = 1 OR b = 2").and(col("c").eq(1)))
+            .filter(not(raw("d = 1 OR e = 2")))
+            .build();
+        assert_eq!(
+            "DELETE FROM t WHERE (a = 1 OR b = 2) AND c = $1 AND NOT (d = 1 OR e = 2);",
+            query
+        );
+
+        let (query, _) = query_builder::select("t").filter("a = 1 OR b = 2").build();
+        assert_eq!("SELECT * FROM t WHERE a = 1 OR b = 2;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_expression_having() {
+        let (query, params) = query_builder::select("posts")
+            .fields(&["user_id", "COUNT(*) AS total"])
+            .group_by("user_id")
+            .having(raw("COUNT(*)").ge(5).or(col("user_id").eq(1)))
+            .build();
+        assert_eq!(
+            "SELECT user_id, COUNT(*) AS total FROM posts GROUP BY user_id \
+             HAVING (COUNT(*)) >= $1 OR user_id = $2;",
+            query
+        );
+        assert_eq!(vec![Value::Int(5), Value::Int(1)], params);
+    }
+
+    struct User {
+        name: &'static str,
+        karma: i64,
+    }
+
+    impl<'a> Row<'a> for User {
+        fn into_fields(self) -> Vec<(&'a str, Expr<'a>)> {
+            vec![("name", val(self.name)), ("karma", val(self.karma))]
+        }
+    }
+
+    #[test]
+    fn test_insert_query_with_rows() {
+        let (query, params) = query_builder::insert("users")
+            .columns(&["name", "karma"])
+            .row(vec![val("Jacob"), val(1)])
+            .rows((2..4).map(|i| vec![val(format!("user{}", i)), val(i)]))
+            .build();
+        assert_eq!(
+            "INSERT INTO users (name, karma) VALUES ($1, $2), ($3, $4), ($5, $6);",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::from("Jacob"),
+                Value::Int(1),
+                Value::from("user2"),
+                Value::Int(2),
+                Value::from("user3"),
+                Value::Int(3),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_records() {
+        let (query, params) = query_builder::insert("users")
+            .set("karma", 0)
+            .set("name", "Jacob")
+            .records(vec![User {
+                name: "Jane",
+                karma: 5,
+            }])
+            .build_for(Dialect::Sqlite)
+            .unwrap();
+        assert_eq!(
+            "INSERT INTO users (karma, name) VALUES (?, ?), (?, ?);",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::Int(0),
+                Value::from("Jacob"),
+                Value::Int(5),
+                Value::from("Jane"),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_mismatched_rows() {
+        let mut builder = query_builder::insert("users");
+        builder
+            .columns(&["name", "karma"])
+            .row(vec![val("Jacob"), val(1)])
+            .row(vec![val("Jane")]);
+        assert_eq!(
+            Err(BuildError::RowMismatch { row: 1 }),
+            builder.build_for(Dialect::Postgres)
+        );
+
+        let mut builder = query_builder::insert("users");
+        builder.set("name", "Jacob").record(User {
+            name: "Jane",
+            karma: 5,
+        });
+        assert_eq!(
+            Err(BuildError::RowMismatch { row: 1 }),
+            builder.build_for(Dialect::Postgres)
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_upsert() {
+        let mut builder = query_builder::insert("users");
+        builder
+            .set("email", "jacob@example.com")
+            .set("karma", 1)
+            .on_conflict(&["email"])
+            .do_update("karma", excluded("karma"))
+            .do_update_filter(col("users.locked").eq(false));
+        let (query, params) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "INSERT INTO users (email, karma) VALUES ($1, $2) ON CONFLICT (email) \
+             DO UPDATE SET karma = EXCLUDED.karma WHERE users.locked = $3;",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::from("jacob@example.com"),
+                Value::Int(1),
+                Value::Bool(false),
+            ],
+            params
+        );
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!(
+            "INSERT INTO users (email, karma) VALUES (?, ?) ON CONFLICT (email) \
+             DO UPDATE SET karma = EXCLUDED.karma WHERE users.locked = ?;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "ON DUPLICATE KEY UPDATE with WHERE",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+
+        let (query, _) = query_builder::insert("users")
+            .set("email", "jacob@example.com")
+            .set("karma", 1)
+            .on_conflict(&["email"])
+            .do_update("karma", excluded("karma"))
+            .build_for(Dialect::MySql)
+            .unwrap();
+        assert_eq!(
+            "INSERT INTO users (email, karma) VALUES (?, ?) \
+             ON DUPLICATE KEY UPDATE karma = VALUES(karma);",
+            query
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_upsert_do_nothing() {
+        let mut builder = query_builder::insert("users");
+        builder
+            .set("email", "jacob@example.com")
+            .on_conflict_constraint("users_email_key")
+            .do_nothing();
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "INSERT INTO users (email) VALUES ($1) \
+             ON CONFLICT ON CONSTRAINT users_email_key DO NOTHING;",
+            query
+        );
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "INSERT INTO users (email) VALUES (?) ON DUPLICATE KEY UPDATE email = email;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "ON CONFLICT ON CONSTRAINT",
+            }),
+            builder.build_for(Dialect::Sqlite)
+        );
+
+        let (query, _) = query_builder::insert("users")
+            .set("email", "jacob@example.com")
+            .on_conflict(&[])
+            .build_for(Dialect::Sqlite)
+            .unwrap();
+        assert_eq!(
+            "INSERT INTO users (email) VALUES (?) ON CONFLICT DO NOTHING;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_select() {
+        let mut archived = query_builder::select("posts");
+        archived
+            .fields(&["id", "title"])
+            .filter(col("created_at").lt("2000-01-01"));
+        let (query, params) = query_builder::insert("archive")
+            .columns(&["post_id", "title"])
+            .select(archived)
+            .returning("post_id")
+            .build();
+        assert_eq!(
+            "INSERT INTO archive (post_id, title) SELECT id, title FROM posts \
+             WHERE created_at < $1 RETURNING post_id;",
+            query
+        );
+        assert_eq!(vec![Value::from("2000-01-01")], params);
+
+        let (query, _) = query_builder::insert("archive")
+            .columns(&["post_id", "title"])
+            .select(query_builder::select("posts"))
+            .on_conflict(&["post_id"])
+            .build_for(Dialect::Sqlite)
+            .unwrap();
+        assert_eq!(
+            "INSERT INTO archive (post_id, title) SELECT * FROM posts WHERE true \
+             ON CONFLICT (post_id) DO NOTHING;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_insert_query_with_invalid_select() {
+        let mut builder = query_builder::insert("archive");
+        builder.select(query_builder::select("posts"));
+        assert_eq!(
+            Err(BuildError::MissingColumns),
+            builder.build_for(Dialect::Postgres)
+        );
+
+        let mut posts = query_builder::select("posts");
+        posts.fields(&["id"]);
+        builder.columns(&["post_id", "title"]).select(posts);
+        assert_eq!(
+            Err(BuildError::ColumnCountMismatch {
+                expected: 2,
+                found: 1,
+            }),
+            builder.build_for(Dialect::Postgres)
+        );
+
+        builder
+            .select(query_builder::select("posts"))
+            .row(vec![1, 2]);
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "INSERT ... SELECT",
+                with: "VALUES",
+            }),
+            builder.build_for(Dialect::Postgres)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_derived_tables() {
+        let mut totals = query_builder::select("posts");
+        totals
+            .fields(&["user_id", "COUNT(*) AS total"])
+            .filter(col("published").eq(true))
+            .group_by("user_id");
+        let mut recent = query_builder::select("logins");
+        recent
+            .fields(&["user_id"])
+            .filter(col("created_at").gt("2018-01-01"));
+        let (query, params) = query_builder::select(derived(totals, "t"))
+            .fields(&["t.user_id", "t.total"])
+            .join(
+                derived(recent, "r"),
+                "r.user_id",
+                "t.user_id",
+                query_builder::Join::Inner,
+            )
+            .filter(col("t.total").gt(10))
+            .build();
+        assert_eq!(
+            "SELECT t.user_id, t.total FROM (SELECT user_id, COUNT(*) AS total FROM posts \
+             WHERE published = $1 GROUP BY user_id) AS t INNER JOIN (SELECT user_id FROM logins \
+             WHERE created_at > $2) AS r ON r.user_id = t.user_id WHERE t.total > $3;",
+            query
+        );
+        assert_eq!(
+            vec![Value::Bool(true), Value::from("2018-01-01"), Value::Int(10)],
+            params
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_subquery_expressions() {
+        let mut banned = query_builder::select("bans");
+        banned.fields(&["user_id"]).filter(col("active").eq(true));
+        let mut posts = query_builder::select("posts");
+        posts.fields(&["1"]).filter("posts.user_id = users.id");
+        let mut post_count = query_builder::select("posts");
+        post_count
+            .fields(&["COUNT(*)"])
+            .filter("posts.user_id = users.id");
+        let (query, params) = query_builder::select("users")
+            .fields(&["id"])
+            .field_as(subquery(post_count), "post_count")
+            .filter(not(col("id").in_subquery(banned)))
+            .filter(exists(posts))
+            .build();
+        assert_eq!(
+            "SELECT id, (SELECT COUNT(*) FROM posts WHERE posts.user_id = users.id) AS post_count \
+             FROM users WHERE NOT (id IN (SELECT user_id FROM bans WHERE active = $1)) \
+             AND EXISTS (SELECT 1 FROM posts WHERE posts.user_id = users.id);",
+            query
+        );
+        assert_eq!(vec![Value::Bool(true)], params);
+    }
+
+    #[test]
+    fn test_update_query_with_subquery() {
+        let mut karma = query_builder::select("votes");
+        karma
+            .fields(&["SUM(value)"])
+            .filter(col("votes.created_at").gt("2018-01-01"))
+            .filter("votes.user_id = users.id");
+        let (query, params) = query_builder::update("users")
+            .set("karma", subquery(karma))
+            .filter(col("id").eq(7))
+            .build_for(Dialect::MySql)
+            .unwrap();
+        assert_eq!(
+            "UPDATE users SET karma = (SELECT SUM(value) FROM votes WHERE votes.created_at > ? \
+             AND (votes.user_id = users.id)) WHERE id = ?;",
+            query
+        );
+        assert_eq!(vec![Value::from("2018-01-01"), Value::Int(7)], params);
+    }
+
+    #[test]
+    fn test_select_query_with_ctes() {
+        let mut recent = query_builder::select("posts");
+        recent
+            .fields(&["user_id", "COUNT(*) AS total"])
+            .filter(col("created_at").gt("2018-01-01"))
+            .group_by("user_id");
+        let mut active = query_builder::select("recent");
+        active.fields(&["user_id"]).filter(col("total").ge(5));
+        let (query, params) = query_builder::select("users")
+            .with("recent", recent)
+            .with("active", active)
+            .fields(&["users.name", "recent.total"])
+            .join(
+                "recent",
+                "recent.user_id",
+                "users.id",
+                query_builder::Join::Inner,
+            )
+            .filter(col("users.id").in_subquery(query_builder::select("active")))
+            .build();
+        assert_eq!(
+            "WITH recent AS (SELECT user_id, COUNT(*) AS total FROM posts WHERE created_at > $1 \
+             GROUP BY user_id), active AS (SELECT user_id FROM recent WHERE total >= $2) \
+             SELECT users.name, recent.total FROM users INNER JOIN recent \
+             ON recent.user_id = users.id WHERE users.id IN (SELECT * FROM active);",
+            query
+        );
+        assert_eq!(vec![Value::from("2018-01-01"), Value::Int(5)], params);
+    }
+
+    #[test]
+    fn test_select_query_with_recursive_cte() {
+        let mut builder = query_builder::select("tree");
+        builder.with_recursive(
+            "tree",
+            &["id", "parent_id"],
+            "SELECT id, parent_id FROM categories WHERE id = 1 UNION ALL \
+             SELECT c.id, c.parent_id FROM categories AS c INNER JOIN tree ON c.parent_id = tree.id",
+        );
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!(
+            "WITH RECURSIVE tree (id, parent_id) AS (SELECT id, parent_id FROM categories \
+             WHERE id = 1 UNION ALL SELECT c.id, c.parent_id FROM categories AS c \
+             INNER JOIN tree ON c.parent_id = tree.id) SELECT * FROM tree;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_dialect_data_modifying_ctes() {
+        let mut archived = query_builder::delete("posts");
+        archived
+            .filter(col("created_at").lt("2000-01-01"))
+            .returning("*");
+        let mut titles = query_builder::select("archived");
+        titles.fields(&["id", "title"]);
+        let mut builder = query_builder::insert("archive");
+        builder
+            .with("archived", archived)
+            .columns(&["post_id", "title"])
+            .select(titles);
+        let (query, params) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "WITH archived AS (DELETE FROM posts WHERE created_at < $1 RETURNING *) \
+             INSERT INTO archive (post_id, title) SELECT id, title FROM archived;",
+            query
+        );
+        assert_eq!(vec![Value::from("2000-01-01")], params);
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "data-modifying statements in WITH",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+
+        let mut titles = query_builder::select("posts");
+        titles.fields(&["id", "title"]);
+        let (query, _) = query_builder::insert("archive")
+            .with("titles", titles)
+            .columns(&["post_id", "title"])
+            .select(query_builder::select("titles"))
+            .build_for(Dialect::MySql)
+            .unwrap();
+        assert_eq!(
+            "INSERT INTO archive (post_id, title) WITH titles AS (SELECT id, title FROM posts) \
+             SELECT * FROM titles;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_compound_query() {
+        let mut posts = query_builder::select("posts");
+        posts
+            .fields(&["id", "created_at"])
+            .filter(col("author_id").eq(1));
+        let mut comments = query_builder::select("comments");
+        comments
+            .fields(&["post_id", "created_at"])
+            .filter(col("author_id").eq(1));
+        let mut likes = query_builder::select("likes");
+        likes.fields(&["post_id", "created_at"]);
+        let mut builder = compound(posts);
+        builder
+            .union(comments)
+            .union_all(likes)
+            .order_by("created_at", query_builder::Order::Desc)
+            .limit(20);
+        let (query, params) = builder.build();
+        assert_eq!(
+            "SELECT id, created_at FROM posts WHERE author_id = $1 UNION \
+             SELECT post_id, created_at FROM comments WHERE author_id = $2 UNION ALL \
+             SELECT post_id, created_at FROM likes ORDER BY created_at DESC LIMIT 20;",
+            query
+        );
+        assert_eq!(vec![Value::Int(1), Value::Int(1)], params);
+
+        let mut recent = query_builder::select("posts");
+        recent
+            .fields(&["id"])
+            .order_by("id", query_builder::Order::Desc)
+            .limit(5);
+        let mut hidden = query_builder::select("hidden");
+        hidden.fields(&["post_id"]);
+        let mut builder = compound(recent);
+        builder.except(hidden);
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "(SELECT id FROM posts ORDER BY id DESC LIMIT 5) EXCEPT SELECT post_id FROM hidden;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "ORDER BY, LIMIT or WITH in a compound SELECT",
+            }),
+            builder.build_for(Dialect::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_compound_query_with_mismatched_columns() {
+        let mut users = query_builder::select("users");
+        users.fields(&["id", "name"]);
+        let mut admins = query_builder::select("admins");
+        admins.fields(&["id"]);
+        let mut builder = compound(query_builder::select("guests"));
+        builder.intersect(users).intersect(admins);
+        assert_eq!(
+            Err(BuildError::ColumnCountMismatch {
+                expected: 2,
+                found: 1,
+            }),
+            builder.build_for(Dialect::Postgres)
+        );
+
+        let mut locked = query_builder::select("t");
+        locked.fields(&["id"]).lock(for_update());
+        let mut builder = compound(locked);
+        builder.union(query_builder::select("u").fields(&["id"]).clone());
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "FOR UPDATE or FOR SHARE",
+                with: "UNION, INTERSECT or EXCEPT",
+            }),
+            builder.try_build()
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_recursive_compound_cte() {
+        let mut root = query_builder::select("categories");
+        root.fields(&["id", "parent_id"]).filter(col("id").eq(1));
+        let mut children = query_builder::select("categories");
+        children
+            .alias("categories", "c")
+            .fields(&["c.id", "c.parent_id"])
+            .join("tree", "c.parent_id", "tree.id", query_builder::Join::Inner);
+        let mut tree = compound(root);
+        tree.union_all(children);
+        let (query, _) = query_builder::select("tree")
+            .with_recursive("tree", &["id", "parent_id"], tree)
+            .build();
+        assert_eq!(
+            "WITH RECURSIVE tree (id, parent_id) AS (SELECT id, parent_id FROM categories \
+             WHERE id = $1 UNION ALL SELECT c.id, c.parent_id FROM categories AS c \
+             INNER JOIN tree ON c.parent_id = tree.id) SELECT * FROM tree;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_window_functions() {
+        let mut by_author = window();
+        by_author
+            .partition_by("author_id")
+            .order_by("created_at", Order::Desc);
+        let mut running = window();
+        running
+            .order_by("created_at", Order::Asc)
+            .rows(FrameBound::UnboundedPreceding, FrameBound::CurrentRow);
+        let (query, _) = query_builder::select("posts")
+            .fields(&["id"])
+            .field_as(raw("ROW_NUMBER()").over(by_author), "rank")
+            .field_as(raw("SUM(karma)").over(running), "total")
+            .build_for(Dialect::Sqlite)
+            .unwrap();
+        assert_eq!(
+            "SELECT id, ROW_NUMBER() OVER (PARTITION BY author_id ORDER BY created_at DESC) AS rank, \
+             SUM(karma) OVER (ORDER BY created_at ASC ROWS BETWEEN UNBOUNDED PRECEDING \
+             AND CURRENT ROW) AS total FROM posts;",
+            query
+        );
+
+        let frames = [
+            (
+                FrameBound::UnboundedFollowing,
+                FrameBound::UnboundedPreceding,
+            ),
+            (
+                FrameBound::UnboundedFollowing,
+                FrameBound::UnboundedFollowing,
+            ),
+            (
+                FrameBound::UnboundedPreceding,
+                FrameBound::UnboundedPreceding,
+            ),
+            (FrameBound::CurrentRow, FrameBound::Preceding(1)),
+        ];
+        for &(start, end) in frames.iter() {
+            let mut invalid = window();
+            invalid.rows(start, end);
+            assert_eq!(
+                Err(BuildError::InvalidFrame),
+                query_builder::select("posts")
+                    .field(raw("SUM(karma)").over(invalid))
+                    .try_build()
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_query_with_named_windows() {
+        let mut by_author = window();
+        by_author.partition_by("author_id");
+        let mut nearby = Window::named("w");
+        nearby
+            .order_by("karma", Order::Asc)
+            .range(FrameBound::Preceding(10), FrameBound::Following(10));
+        let (query, _) = query_builder::select("posts")
+            .fields(&["id"])
+            .field_as(raw("COUNT(*)").over(Window::named("w")), "author_posts")
+            .field_as(raw("COUNT(*)").over(nearby), "similar")
+            .window("w", by_author)
+            .order_by(raw("RANK()").over(Window::named("w")), Order::Asc)
+            .build();
+        assert_eq!(
+            "SELECT id, COUNT(*) OVER w AS author_posts, COUNT(*) OVER (w ORDER BY karma ASC \
+             RANGE BETWEEN 10 PRECEDING AND 10 FOLLOWING) AS \"similar\" FROM posts \
+             WINDOW w AS (PARTITION BY author_id) ORDER BY RANK() OVER w ASC;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_join_types() {
+        let mut builder = query_builder::select("users");
+        builder
+            .join_on(
+                "prices",
+                col("prices.product_id")
+                    .eq(col("orders.product_id"))
+                    .and(col("orders.created_at").ge(col("prices.valid_from")))
+                    .and(col("orders.created_at").lt(col("prices.valid_to"))),
+                Join::Right,
+            )
+            .join_using("orders", &["user_id"], Join::Full)
+            .natural_join("profiles", Join::Left)
+            .cross_join("settings");
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "SELECT * FROM users RIGHT JOIN prices ON prices.product_id = orders.product_id \
+             AND orders.created_at >= prices.valid_from AND orders.created_at < prices.valid_to \
+             FULL OUTER JOIN orders USING (user_id) NATURAL LEFT JOIN profiles \
+             CROSS JOIN settings;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "FULL OUTER JOIN",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+
+        let mut builder = query_builder::select("users");
+        builder.join_using("settings", &["user_id"], Join::Cross);
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "CROSS JOIN",
+                with: "a join condition",
+            }),
+            builder.build_for(Dialect::Postgres)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_lateral_join() {
+        let mut latest = query_builder::select("posts");
+        latest
+            .fields(&["title"])
+            .filter("posts.user_id = users.id")
+            .order_by("created_at", Order::Desc)
+            .limit(3);
+        let mut builder = query_builder::select("users");
+        builder.fields(&["users.name", "p.title"]).join_on(
+            lateral(latest, "p"),
+            raw("true"),
+            Join::Left,
+        );
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "SELECT users.name, p.title FROM users LEFT JOIN LATERAL (SELECT title FROM posts \
+             WHERE posts.user_id = users.id ORDER BY created_at DESC LIMIT 3) AS p ON true;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "LATERAL",
+            }),
+            builder.build_for(Dialect::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_multiple_orders() {
+        let (query, _) = query_builder::select("users")
+            .fields(&["id", "name"])
+            .order_by("karma", Order::Desc)
+            .order_by("id", Order::Asc)
+            .build();
+        assert_eq!(
+            "SELECT id, name FROM users ORDER BY karma DESC, id ASC;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_dialect_order_nulls() {
+        let mut builder = query_builder::select("users");
+        builder
+            .order_by_key(desc("last_login").nulls(Nulls::Last))
+            .order_by_key(asc(col("karma").gt(10)).nulls(Nulls::First));
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!(
+            "SELECT * FROM users ORDER BY last_login DESC NULLS LAST, \
+             karma > ? ASC NULLS FIRST;",
+            query
+        );
+        let (query, params) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "SELECT * FROM users ORDER BY last_login IS NULL, last_login DESC, \
+             (karma > ?) IS NOT NULL, karma > ? ASC;",
+            query
+        );
+        // The key is written twice, so its values are bound twice
+        assert_eq!(vec![Value::Int(10), Value::Int(10)], params);
+
+        let mut builder = query_builder::select("users");
+        builder.order_by_key(OrderBy::position(1, Order::Asc).nulls(Nulls::First));
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "NULLS FIRST or LAST by column position",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_collation_and_position() {
+        let mut builder = query_builder::select("users");
+        builder
+            .fields(&["name", "COUNT(*)"])
+            .group_by("name")
+            .order_by_key(OrderBy::position(2, Order::Desc))
+            .order_by_key(asc("name").collate("C"));
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "SELECT name, COUNT(*) FROM users GROUP BY name ORDER BY 2 DESC, \
+             name COLLATE \"C\" ASC;",
+            query
+        );
+        let (query, _) = builder.build_for(Dialect::Sqlite).unwrap();
+        assert_eq!(
+            "SELECT name, COUNT(*) FROM users GROUP BY name ORDER BY 2 DESC, name COLLATE C ASC;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_distinct() {
+        let mut builder = query_builder::select("posts");
+        builder.distinct().fields(&["author_id"]);
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!("SELECT DISTINCT author_id FROM posts;", query);
+    }
+
+    #[test]
+    fn test_select_query_with_distinct_on() {
+        let mut builder = query_builder::select("posts");
+        builder
+            .distinct_on(vec!["author_id"])
+            .fields(&["author_id", "title"])
+            .order_by("author_id", Order::Asc)
+            .order_by("created_at", Order::Desc);
+        let (query, _) = builder.build();
+        assert_eq!(
+            "SELECT DISTINCT ON (author_id) author_id, title FROM posts \
+             ORDER BY author_id ASC, created_at DESC;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "DISTINCT ON",
+            }),
+            builder.build_for(Dialect::Sqlite)
+        );
+
+        let mut builder = query_builder::select("posts");
+        builder
+            .distinct_on(vec!["author_id"])
+            .order_by("created_at", Order::Desc)
+            .order_by("author_id", Order::Asc);
+        assert_eq!(
+            Err(BuildError::DistinctOnOrder),
+            builder.build_for(Dialect::Postgres)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_lock() {
+        let mut builder = query_builder::select("jobs");
+        builder
+            .filter(col("status").eq("pending"))
+            .order_by("id", Order::Asc)
+            .limit(10)
+            .lock(for_update().skip_locked());
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "SELECT * FROM jobs WHERE status = $1 ORDER BY id ASC LIMIT 10 FOR UPDATE SKIP LOCKED;",
+            query
+        );
+        let (query, _) = builder.build_for(Dialect::MySql).unwrap();
+        assert_eq!(
+            "SELECT * FROM jobs WHERE status = ? ORDER BY id ASC LIMIT 10 FOR UPDATE SKIP LOCKED;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "FOR UPDATE or FOR SHARE",
+            }),
+            builder.build_for(Dialect::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_select_query_with_locks_of_tables() {
+        let mut builder = query_builder::select("jobs");
+        builder
+            .join("workers", "workers.id", "jobs.worker_id", Join::Inner)
+            .lock(Lock::new(LockStrength::NoKeyUpdate).of(&["jobs"]).nowait())
+            .lock(for_share().of(&["workers"]));
+        let (query, _) = builder.build_for(Dialect::Postgres).unwrap();
+        assert_eq!(
+            "SELECT * FROM jobs INNER JOIN workers ON workers.id = jobs.worker_id \
+             FOR NO KEY UPDATE OF jobs NOWAIT FOR SHARE OF workers;",
+            query
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "FOR NO KEY UPDATE",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+    }
+
+    #[test]
+    fn test_try_build_with_invalid_queries() {
+        assert_eq!(
+            Err(BuildError::EmptyColumns { clause: "SELECT" }),
+            query_builder::select("users").fields(&[]).try_build()
+        );
+        assert_eq!(
+            Err(BuildError::MissingClause {
+                clause: "HAVING",
+                requires: "GROUP BY",
+            }),
+            query_builder::select("users")
+                .having("COUNT(*) > 1")
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::MissingClause {
+                clause: "INSERT",
+                requires: "VALUES or SELECT",
+            }),
+            query_builder::insert("users").try_build()
+        );
+        assert_eq!(
+            Err(BuildError::EmptyColumns { clause: "INSERT" }),
+            query_builder::insert("users")
+                .columns(&[])
+                .row(Vec::<Value>::new())
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::MissingClause {
+                clause: "UPDATE",
+                requires: "SET",
+            }),
+            query_builder::update("users")
+                .filter(col("id").eq(1))
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::EmptyColumns { clause: "USING" }),
+            query_builder::select("users")
+                .join_using("posts", &[], Join::Inner)
+                .try_build()
+        );
+    }
+
+    #[test]
+    fn test_try_build() {
+        let (query, params) = query_builder::delete("users")
+            .filter(col("id").eq(1))
+            .try_build()
+            .unwrap();
+        assert_eq!("DELETE FROM users WHERE id = $1;", query);
+        assert_eq!(vec![Value::Int(1)], params);
+    }
+
+    #[test]
+    #[should_panic(expected = "UPDATE requires SET")]
+    fn test_build_with_invalid_query() {
+        query_builder::update("users").build();
+    }
+
+    fn tenant_posts(tenant: &str, columns: &[&str]) -> query_builder::Select<'static> {
+        let mut builder = query_builder::select(format!("{}.posts", tenant));
+        for column in columns {
+            builder.field(format!("posts.{}", column));
+        }
+        builder
+            .filter_with(format!("{}.posts.deleted = ?", tenant), vec![false.into()])
+            .order_by(String::from("posts.id"), Order::Asc);
+        builder
+    }
+
+    #[test]
+    fn test_owned_select_query() {
+        let builder = tenant_posts("acme", &["id", "title"]);
+        let (query, params) = ::std::thread::spawn(move || builder.build())
+            .join()
+            .unwrap();
+        assert_eq!(
+            "SELECT posts.id, posts.title FROM acme.posts WHERE acme.posts.deleted = $1 \
+             ORDER BY posts.id ASC;",
+            query
+        );
+        assert_eq!(vec![Value::Bool(false)], params);
+    }
+
+    #[test]
+    fn test_owned_insert_query() {
+        let mut builder = query_builder::insert(String::from("audit.events"));
+        for i in 0..2 {
+            builder.set(format!("field{}", i), i);
+        }
+        builder
+            .set_raw(
+                String::from("created_at"),
+                format!("NOW() - INTERVAL '{} days'", 1),
+            )
+            .returning(String::from("id"));
+        let (query, _) = builder.build();
+        assert_eq!(
+            "INSERT INTO audit.events (field0, field1, created_at) \
+             VALUES ($1, $2, NOW() - INTERVAL '1 days') RETURNING id;",
+            query
+        );
+    }
+
+    #[test]
+    fn test_clone_select_query() {
+        let mut base = query_builder::select("users");
+        base.fields(&["id", "name"]).filter(col("active").eq(true));
+
+        let mut recent = base.clone();
+        recent.order_by("id", Order::Desc).limit(10);
+        let mut admins = base.clone();
+        admins.filter(col("role").eq("admin"));
+
+        let (query, _) = base.build();
+        assert_eq!("SELECT id, name FROM users WHERE active = $1;", query);
+        let (query, _) = recent.build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE active = $1 ORDER BY id DESC LIMIT 10;",
+            query
+        );
+        let (query, params) = admins.build();
+        assert_eq!(
+            "SELECT id, name FROM users WHERE active = $1 AND role = $2;",
+            query
+        );
+        assert_eq!(vec![Value::from(true), Value::from("admin")], params);
     }
 
     #[test]
-    fn test_select_query_with_limit() {
-        let query = query_builder::select("users")
+    fn test_eq_hash_queries() {
+        use std::collections::HashSet;
+
+        let mut first = query_builder::update("users");
+        first.set("karma", 1.5).filter(col("id").eq(1));
+        let mut second = query_builder::update("users");
+        second.set("karma", 1.5).filter(col("id").eq(1));
+        let mut third = second.clone();
+        third.returning("id");
+
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+
+        let mut set = HashSet::new();
+        set.insert(first);
+        set.insert(second);
+        set.insert(third);
+        assert_eq!(2, set.len());
+        assert_eq!(Join::Left, Join::Left.clone());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_select_query() {
+        let mut builder = query_builder::select("users");
+        builder
             .fields(&["id", "name"])
-            .limit(15)
+            .filter_with("name = ?", vec![Value::from("Jacob")])
+            .order_by("id", Order::Asc);
+
+        let json = super::serde_json::to_string(&builder).unwrap();
+        let restored: query_builder::Select = super::serde_json::from_str(&json).unwrap();
+        assert_eq!(builder, restored);
+        assert_eq!(builder.build(), restored.build());
+
+        let json = json.replace(r#"{"Text":"Jacob"}"#, r#"{"Text":"Jacob"},{"Text":"Jane"}"#);
+        let restored: query_builder::Select = super::serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Err(BuildError::BoundValueMismatch {
+                markers: 1,
+                values: 2,
+            }),
+            restored.try_build()
+        );
+    }
+
+    struct Tenant(i64);
+
+    impl<'a> IntoScope<'a> for &'a Tenant {
+        fn into_scope(self) -> Scope<'a> {
+            let mut scope = scope();
+            scope.filter(col("tenant_id").eq(self.0));
+            scope
+        }
+    }
+
+    #[test]
+    fn test_scope_queries() {
+        let tenant = Tenant(7);
+        let mut live = scope();
+        live.filter(col("deleted_at").is_null());
+
+        let (query, params) = query_builder::select("posts")
+            .scope(&tenant)
+            .scope(&live)
+            .filter(col("published").eq(true))
             .build();
-        assert_eq!("SELECT id, name FROM users LIMIT 15;", query);
+        assert_eq!(
+            "SELECT * FROM posts WHERE tenant_id = $1 AND deleted_at IS NULL AND published = $2;",
+            query
+        );
+        assert_eq!(vec![Value::Int(7), Value::Bool(true)], params);
+
+        let (query, params) = query_builder::update("posts")
+            .set("title", "Hello")
+            .scope(&tenant)
+            .scope(&live)
+            .build();
+        assert_eq!(
+            "UPDATE posts SET title = $1 WHERE tenant_id = $2 AND deleted_at IS NULL;",
+            query
+        );
+        assert_eq!(vec![Value::from("Hello"), Value::Int(7)], params);
+
+        let (query, _) = query_builder::delete("posts").scope(&tenant).build();
+        assert_eq!("DELETE FROM posts WHERE tenant_id = $1;", query);
     }
 
     #[test]
-    fn test_select_query_with_offset() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name"])
-            .limit(15)
-            .offset(30)
+    fn test_scope_with_joins_and_order() {
+        let mut visible = scope();
+        visible
+            .join("users", "users.id", "posts.user_id", Join::Inner)
+            .filter(col("users.banned").eq(false))
+            .order_by("posts.id", Order::Desc);
+        let mut active = scope();
+        active
+            .join("users", "users.id", "posts.user_id", Join::Inner)
+            .filter(col("users.active").eq(true));
+
+        let (query, _) = query_builder::select("posts")
+            .scope(&visible)
+            .scope(active)
             .build();
-        assert_eq!("SELECT id, name FROM users LIMIT 15 OFFSET 30;", query);
+        assert_eq!(
+            "SELECT * FROM posts INNER JOIN users ON users.id = posts.user_id \
+             WHERE users.banned = $1 AND users.active = $2 ORDER BY posts.id DESC;",
+            query
+        );
     }
 
     #[test]
-    fn test_select_query_with_group() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name", "MAX(karma) AS max"])
-            .group_by("name")
-            .having("max > 100")
+    fn test_scope_with_or_conditions() {
+        let mut tenant = scope();
+        tenant
+            .filter(col("tenant_id").eq(7))
+            .filter("archived = false OR pinned = true");
+
+        let (query, params) = query_builder::select("t")
+            .scope(&tenant)
+            .filter("a = 1 OR b = 2")
             .build();
         assert_eq!(
-            "SELECT id, name, MAX(karma) AS max FROM users GROUP BY name HAVING max > 100;",
+            "SELECT * FROM t WHERE tenant_id = $1 AND (archived = false OR pinned = true) \
+             AND (a = 1 OR b = 2);",
+            query
+        );
+        assert_eq!(vec![Value::Int(7)], params);
+
+        let (query, _) = query_builder::delete("t")
+            .filter_with("a = ? OR b = ?", vec![Value::Int(1), Value::Int(2)])
+            .scope(&Tenant(7))
+            .build();
+        assert_eq!(
+            "DELETE FROM t WHERE (a = $1 OR b = $2) AND tenant_id = $3;",
             query
         );
     }
 
     #[test]
-    fn test_select_query_with_conditions() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name"])
-            .filter("id = $1")
-            .filter("name = $2")
+    fn test_scope_with_joins_on_update_and_delete() {
+        let mut visible = scope();
+        visible.join("users", "users.id", "posts.user_id", Join::Inner);
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "JOIN",
+                with: "DELETE",
+            }),
+            query_builder::delete("posts").scope(&visible).try_build()
+        );
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "JOIN",
+                with: "UPDATE",
+            }),
+            query_builder::update("posts")
+                .set("title", "Hello")
+                .scope(&visible)
+                .try_build()
+        );
+    }
+
+    #[test]
+    fn test_create_table_query() {
+        let (query, params) = query_builder::create_table("posts")
+            .if_not_exists()
+            .column(
+                column("id", DataType::BigInt)
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .column(column("title", DataType::VarChar(200)).not_null())
+            .column(
+                column("user_id", DataType::BigInt)
+                    .not_null()
+                    .references(references("users", &["id"]).on_delete(ReferentialAction::Cascade)),
+            )
+            .column(column("status", DataType::Text).not_null().default("draft"))
+            .column(column("score", DataType::Double).default(0.5))
+            .column(column("karma", DataType::Integer).check(col("karma").ge(0)))
+            .column(column("created", DataType::Timestamp).default(raw("CURRENT_TIMESTAMP")))
+            .unique(&["user_id", "title"])
+            .check("score <= 1")
             .build();
         assert_eq!(
-            "SELECT id, name FROM users WHERE id = $1 AND name = $2;",
+            "CREATE TABLE IF NOT EXISTS posts (id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \
+             title VARCHAR(200) NOT NULL, \
+             user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE, \
+             status TEXT NOT NULL DEFAULT 'draft', score DOUBLE PRECISION DEFAULT 0.5, \
+             karma INTEGER CHECK (karma >= 0), created TIMESTAMP DEFAULT CURRENT_TIMESTAMP, \
+             UNIQUE (user_id, title), CHECK (score <= 1));",
             query
         );
+        assert!(params.is_empty());
     }
 
     #[test]
-    fn test_select_query_with_order() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name"])
-            .filter("name = $1")
-            .order_by("id", query_builder::Order::Asc)
+    fn test_create_table_query_for_dialects() {
+        let mut builder = query_builder::create_table("user");
+        builder
+            .column(
+                column("id", DataType::BigInt)
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .column(column("team_id", DataType::Integer).references(references("teams", &["id"])))
+            .column(column("name", DataType::Text).default("O'Brien \\ Co"))
+            .column(column("active", DataType::Boolean).default(true))
+            .column(column("settings", DataType::Json));
+
+        assert_eq!(
+            "CREATE TABLE `user` (id BIGINT AUTO_INCREMENT PRIMARY KEY, team_id INTEGER, \
+             name TEXT DEFAULT 'O''Brien \\\\ Co', active BOOLEAN DEFAULT TRUE, settings JSON, \
+             FOREIGN KEY (team_id) REFERENCES teams (id));",
+            builder.build_for(Dialect::MySql).unwrap().0
+        );
+        assert_eq!(
+            "CREATE TABLE \"user\" (id INTEGER PRIMARY KEY, team_id INTEGER REFERENCES teams (id), \
+             name TEXT DEFAULT 'O''Brien \\ Co', active BOOLEAN DEFAULT 1, settings TEXT);",
+            builder.build_for(Dialect::Sqlite).unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_create_table_query_errors() {
+        assert_eq!(
+            Err(BuildError::EmptyColumns {
+                clause: "CREATE TABLE",
+            }),
+            query_builder::create_table("users").try_build()
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "auto-increment columns without PRIMARY KEY",
+            }),
+            query_builder::create_table("users")
+                .column(column("id", DataType::Integer).auto_increment())
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "PRIMARY KEY",
+                with: "another PRIMARY KEY",
+            }),
+            query_builder::create_table("users")
+                .column(column("id", DataType::Integer).primary_key())
+                .primary_key(&["id"])
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::ColumnCountMismatch {
+                expected: 2,
+                found: 1,
+            }),
+            query_builder::create_table("posts")
+                .column(column("user_id", DataType::Integer))
+                .column(column("team_id", DataType::Integer))
+                .foreign_key(&["user_id", "team_id"], references("users", &["id"]))
+                .try_build()
+        );
+    }
+
+    #[test]
+    fn test_alter_table_query() {
+        let (query, params) = alter_table("users")
+            .add_column(column("karma", DataType::Integer).not_null().default(0))
+            .drop_column("legacy")
+            .alter_type("name", DataType::VarChar(100))
+            .set_default("active", true)
+            .drop_default("role")
+            .set_not_null("email")
+            .drop_not_null("bio")
+            .add_unique("users_email_key", &["email"])
+            .add_foreign_key(
+                "users_team_fkey",
+                &["team_id"],
+                references("teams", &["id"]).on_delete(ReferentialAction::SetNull),
+            )
+            .drop_constraint("users_old_check")
             .build();
         assert_eq!(
-            "SELECT id, name FROM users WHERE name = $1 ORDER BY id ASC;",
+            "ALTER TABLE users ADD COLUMN karma INTEGER NOT NULL DEFAULT 0, DROP COLUMN legacy, \
+             ALTER COLUMN name TYPE VARCHAR(100), ALTER COLUMN active SET DEFAULT TRUE, \
+             ALTER COLUMN role DROP DEFAULT, ALTER COLUMN email SET NOT NULL, \
+             ALTER COLUMN bio DROP NOT NULL, ADD CONSTRAINT users_email_key UNIQUE (email), \
+             ADD CONSTRAINT users_team_fkey FOREIGN KEY (team_id) REFERENCES teams (id) \
+             ON DELETE SET NULL, DROP CONSTRAINT users_old_check;",
+            query
+        );
+        assert!(params.is_empty());
+
+        let (query, _) = alter_table("users")
+            .rename_column("name", "full_name")
+            .build();
+        assert_eq!("ALTER TABLE users RENAME COLUMN name TO full_name;", query);
+        let (query, _) = alter_table("users").rename_to("members").build();
+        assert_eq!("ALTER TABLE users RENAME TO members;", query);
+
+        let (query, _) = alter_table("posts")
+            .add_column(
+                column("user_id", DataType::BigInt).references(references("users", &["id"])),
+            )
+            .rename_column("body", "content")
+            .build_for(Dialect::MySql)
+            .unwrap();
+        assert_eq!(
+            "ALTER TABLE posts ADD COLUMN user_id BIGINT, ADD FOREIGN KEY (user_id) REFERENCES users (id), \
+             RENAME COLUMN body TO content;",
             query
         );
     }
 
     #[test]
-    fn test_select_query_with_join() {
-        let query = query_builder::select("users")
-            .fields(&["id", "name"])
-            .filter("name = $1")
-            .alias("posts", "p")
-            .join("posts", "p.user_id", "users.id", query_builder::Join::Left)
+    fn test_alter_table_query_errors() {
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "changing a column's type",
+            }),
+            alter_table("users")
+                .alter_type("name", DataType::Text)
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "multiple alterations in one ALTER TABLE",
+            }),
+            alter_table("users")
+                .drop_column("a")
+                .drop_column("b")
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "adding a PRIMARY KEY column",
+            }),
+            alter_table("t")
+                .add_column(column("id", DataType::Integer).primary_key().unique())
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "adding a PRIMARY KEY column",
+            }),
+            alter_table("t")
+                .add_column(column("id", DataType::Integer).auto_increment())
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "adding a UNIQUE column",
+            }),
+            alter_table("users")
+                .add_column(column("email", DataType::Text).unique())
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            "ALTER TABLE users ADD COLUMN email TEXT UNIQUE;",
+            alter_table("users")
+                .add_column(column("email", DataType::Text).unique())
+                .build()
+                .0
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "adding a NOT NULL column without a DEFAULT",
+            }),
+            alter_table("users")
+                .add_column(column("karma", DataType::Integer).not_null())
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            "ALTER TABLE users ADD COLUMN karma INTEGER NOT NULL DEFAULT 0;",
+            alter_table("users")
+                .add_column(column("karma", DataType::Integer).not_null().default(0))
+                .build_for(Dialect::Sqlite)
+                .unwrap()
+                .0
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "changing a column's nullability",
+            }),
+            alter_table("users")
+                .set_not_null("email")
+                .build_for(Dialect::MySql)
+        );
+        assert_eq!(
+            Err(BuildError::Incompatible {
+                clause: "RENAME",
+                with: "other alterations",
+            }),
+            alter_table("users")
+                .rename_to("members")
+                .drop_column("legacy")
+                .try_build()
+        );
+        assert_eq!(
+            Err(BuildError::MissingClause {
+                clause: "ALTER TABLE",
+                requires: "an alteration",
+            }),
+            alter_table("users").try_build()
+        );
+    }
+
+    #[test]
+    fn test_drop_query() {
+        let (query, _) = drop_table("users").if_exists().cascade().build();
+        assert_eq!("DROP TABLE IF EXISTS users CASCADE;", query);
+        let (query, _) = drop_view("active_users").build();
+        assert_eq!("DROP VIEW active_users;", query);
+
+        let mut builder = drop_index("users_email_idx");
+        builder.on("users");
+        assert_eq!("DROP INDEX users_email_idx;", builder.build().0);
+        assert_eq!(
+            "DROP INDEX users_email_idx ON users;",
+            builder.build_for(Dialect::MySql).unwrap().0
+        );
+
+        assert_eq!(
+            Err(BuildError::MissingClause {
+                clause: "DROP INDEX",
+                requires: "ON",
+            }),
+            drop_index("users_email_idx").build_for(Dialect::MySql)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "CASCADE",
+            }),
+            drop_table("users").cascade().build_for(Dialect::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_create_index_query() {
+        let (query, params) = create_index("users_email_idx", "users")
+            .unique()
+            .concurrently()
+            .if_not_exists()
+            .expression("lower(email)", Order::Asc)
+            .column("team_id", Order::Desc)
+            .filter(col("deleted").eq(false))
             .build();
-        assert_eq!("SELECT id, name FROM users LEFT JOIN posts AS p ON p.user_id = users.id WHERE name = $1;", query);
+        assert_eq!(
+            "CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS users_email_idx ON users \
+             ((lower(email)) ASC, team_id DESC) WHERE deleted = FALSE;",
+            query
+        );
+        assert!(params.is_empty());
+
+        let (query, _) = create_index("posts_tags_idx", "posts")
+            .using(IndexMethod::Gin)
+            .column("tags", Order::Asc)
+            .build();
+        assert_eq!(
+            "CREATE INDEX posts_tags_idx ON posts USING gin (tags ASC);",
+            query
+        );
+
+        let (query, _) = create_index("posts_order_idx", "posts")
+            .key(asc("position").nulls(Nulls::Last))
+            .build();
+        assert_eq!(
+            "CREATE INDEX posts_order_idx ON posts (position ASC NULLS LAST);",
+            query
+        );
     }
 
     #[test]
-    fn test_update_query() {
-        let query = query_builder::update("users")
-            .set("karma", "0")
-            .set("last_login", "1970-01-01")
+    fn test_create_index_query_for_dialects() {
+        let mut builder = create_index("users_name_idx", "users");
+        builder
+            .using(IndexMethod::BTree)
+            .column("last_name", Order::Asc)
+            .column("first_name", Order::Asc);
+        assert_eq!(
+            "CREATE INDEX users_name_idx ON users (last_name ASC, first_name ASC) USING BTREE;",
+            builder.build_for(Dialect::MySql).unwrap().0
+        );
+        assert_eq!(
+            "CREATE INDEX users_name_idx ON users (last_name ASC, first_name ASC);",
+            builder.build_for(Dialect::Sqlite).unwrap().0
+        );
+
+        builder.filter("active");
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::MySql,
+                feature: "partial indexes",
+            }),
+            builder.build_for(Dialect::MySql)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "CREATE INDEX CONCURRENTLY",
+            }),
+            builder.concurrently().build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::Unsupported {
+                dialect: Dialect::Sqlite,
+                feature: "this index method",
+            }),
+            create_index("posts_tags_idx", "posts")
+                .using(IndexMethod::Gin)
+                .column("tags", Order::Asc)
+                .build_for(Dialect::Sqlite)
+        );
+        assert_eq!(
+            Err(BuildError::EmptyColumns {
+                clause: "CREATE INDEX",
+            }),
+            create_index("users_idx", "users").try_build()
+        );
+    }
+
+    /// An executor that runs statements on an in-memory SQLite database
+    struct SqliteExecutor(rusqlite::Connection);
+
+    impl SqliteExecutor {
+        fn new() -> Self {
+            SqliteExecutor(rusqlite::Connection::open_in_memory().unwrap())
+        }
+
+        /// Whether the database has a table named `name`
+        fn has_table(&self, name: &str) -> bool {
+            self.0
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    [name],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap()
+                == 1
+        }
+    }
+
+    impl Executor for SqliteExecutor {
+        type Error = rusqlite::Error;
+
+        fn dialect(&self) -> Dialect {
+            Dialect::Sqlite
+        }
+
+        fn execute(&mut self, sql: &str) -> Result<(), rusqlite::Error> {
+            self.0.execute_batch(sql)
+        }
+
+        fn query_versions(&mut self, sql: &str) -> Result<Vec<i64>, rusqlite::Error> {
+            let mut statement = self.0.prepare(sql)?;
+            let versions = statement.query_map([], |row| row.get(0))?;
+            versions.collect()
+        }
+    }
+
+    fn blog_migrations() -> Migrations<'static> {
+        let mut migrations = Migrations::new();
+        migrations
+            .add(
+                Migration::new(2, "add karma")
+                    .up(query_builder::alter_table("users")
+                        .add_column(column("karma", DataType::Integer).default(0))
+                        .clone())
+                    .down(
+                        query_builder::alter_table("users")
+                            .drop_column("karma")
+                            .clone(),
+                    ),
+            )
+            .add(
+                Migration::new(1, "create users")
+                    .up(query_builder::create_table("users")
+                        .column(
+                            column("id", DataType::Integer)
+                                .auto_increment()
+                                .primary_key(),
+                        )
+                        .column(column("name", DataType::Text).not_null())
+                        .clone())
+                    .down(query_builder::drop_table("users")),
+            );
+        migrations
+    }
+
+    #[test]
+    fn test_migrations_script() {
+        let migrations = blog_migrations();
+        let up = migrations.up_script(Dialect::Sqlite, &[]).unwrap();
+        assert_eq!(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version BIGINT NOT NULL PRIMARY KEY, \
+             name TEXT NOT NULL, applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP);\n\
+             BEGIN;\n\
+             CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);\n\
+             INSERT INTO schema_migrations (version, name) VALUES (1, 'create users');\n\
+             COMMIT;\n\
+             BEGIN;\n\
+             ALTER TABLE users ADD COLUMN karma INTEGER DEFAULT 0;\n\
+             INSERT INTO schema_migrations (version, name) VALUES (2, 'add karma');\n\
+             COMMIT;",
+            up
+        );
+        let down = migrations.down_script(Dialect::Sqlite, &[1, 2], 0).unwrap();
+        assert_eq!(
+            "BEGIN;\n\
+             ALTER TABLE users DROP COLUMN karma;\n\
+             DELETE FROM schema_migrations WHERE version = 2;\n\
+             COMMIT;\n\
+             BEGIN;\n\
+             DROP TABLE users;\n\
+             DELETE FROM schema_migrations WHERE version = 1;\n\
+             COMMIT;",
+            down
+        );
+
+        // The SQLite scripts run on a real database
+        let mut executor = SqliteExecutor::new();
+        executor.0.execute_batch(&up).unwrap();
+        assert!(executor.has_table("users"));
+        let versions = migrations
+            .versions_query()
+            .build_for(Dialect::Sqlite)
+            .unwrap()
+            .0;
+        assert_eq!(Ok(vec![1, 2]), executor.query_versions(&versions));
+        executor.0.execute_batch(&down).unwrap();
+        assert!(!executor.has_table("users"));
+        assert_eq!(Ok(vec![]), executor.query_versions(&versions));
+
+        assert_eq!(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version BIGINT NOT NULL PRIMARY KEY, \
+             name TEXT NOT NULL, applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP);\n\
+             BEGIN;\n\
+             CREATE TABLE users (id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \
+             name TEXT NOT NULL);\n\
+             INSERT INTO schema_migrations (version, name) VALUES (1, 'create users');\n\
+             COMMIT;\n\
+             BEGIN;\n\
+             ALTER TABLE users ADD COLUMN karma INTEGER DEFAULT 0;\n\
+             INSERT INTO schema_migrations (version, name) VALUES (2, 'add karma');\n\
+             COMMIT;",
+            migrations.up_script(Dialect::Postgres, &[]).unwrap()
+        );
+        assert_eq!(
+            "BEGIN;\n\
+             ALTER TABLE users DROP COLUMN karma;\n\
+             DELETE FROM schema_migrations WHERE version = 2;\n\
+             COMMIT;",
+            migrations
+                .down_script(Dialect::Postgres, &[1, 2], 1)
+                .unwrap()
+        );
+
+        assert_eq!(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version BIGINT NOT NULL PRIMARY KEY, \
+             name TEXT NOT NULL, applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);\n\
+             CREATE TABLE users (id INTEGER AUTO_INCREMENT PRIMARY KEY, name TEXT NOT NULL);\n\
+             INSERT INTO schema_migrations (version, name) VALUES (1, 'create users');\n\
+             ALTER TABLE users ADD COLUMN karma INTEGER DEFAULT 0;\n\
+             INSERT INTO schema_migrations (version, name) VALUES (2, 'add karma');",
+            migrations.up_script(Dialect::MySql, &[]).unwrap()
+        );
+        assert_eq!(
+            "ALTER TABLE users DROP COLUMN karma;\n\
+             DELETE FROM schema_migrations WHERE version = 2;",
+            migrations.down_script(Dialect::MySql, &[1, 2], 1).unwrap()
+        );
+
+        let pending: Vec<_> = migrations
+            .pending(&[1])
+            .unwrap()
+            .iter()
+            .map(|m| m.version())
+            .collect();
+        assert_eq!(vec![2], pending);
+        assert_eq!(
+            Err(MigrationError::UnknownVersion(3)),
+            migrations.pending(&[1, 3])
+        );
+    }
+
+    #[test]
+    fn test_migrations_run() {
+        let mut migrations = blog_migrations();
+        let mut executor = SqliteExecutor::new();
+
+        assert_eq!(Ok(vec![1, 2]), migrations.run(&mut executor));
+        executor
+            .0
+            .execute("INSERT INTO users (name, karma) VALUES ('Jacob', 5)", [])
+            .unwrap();
+        assert_eq!(Ok(vec![]), migrations.run(&mut executor));
+
+        migrations.add(
+            Migration::new(3, "broken")
+                .up(query_builder::create_table("posts")
+                    .column(column("id", DataType::Integer).primary_key())
+                    .clone())
+                .up("ALTER TABLE broken"),
+        );
+        match migrations.run(&mut executor) {
+            Err(MigrationError::Execute {
+                version: Some(3), ..
+            }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        // The migration's transaction was rolled back
+        assert!(!executor.has_table("posts"));
+
+        executor
+            .0
+            .execute(
+                "INSERT INTO schema_migrations (version, name) VALUES (3, 'broken')",
+                [],
+            )
+            .unwrap();
+        assert_eq!(
+            Err(MigrationError::Irreversible(3)),
+            migrations.rollback(&mut executor, 0)
+        );
+        executor
+            .0
+            .execute("DELETE FROM schema_migrations WHERE version = 3", [])
+            .unwrap();
+        assert_eq!(Ok(vec![2, 1]), migrations.rollback(&mut executor, 0));
+        assert!(!executor.has_table("users"));
+
+        migrations.add(Migration::new(1, "create posts"));
+        assert_eq!(
+            Err(MigrationError::DuplicateVersion(1)),
+            migrations.run(&mut executor)
+        );
+    }
+
+    #[test]
+    fn test_migrations_without_transaction() {
+        let mut migrations = blog_migrations();
+        let index = create_index("users_name_idx", "users")
+            .concurrently()
+            .column("name", Order::Asc)
+            .clone();
+        migrations.add(Migration::new(3, "index names").up(index.clone()));
+        assert_eq!(
+            Err(MigrationError::NotTransactional(3)),
+            migrations.up_script(Dialect::Postgres, &[1, 2])
+        );
+
+        let mut migrations = blog_migrations();
+        migrations.add(
+            Migration::new(3, "index names")
+                .up(index)
+                .down(drop_index("users_name_idx"))
+                .without_transaction(),
+        );
+        assert_eq!(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version BIGINT NOT NULL PRIMARY KEY, \
+             name TEXT NOT NULL, applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP);\n\
+             CREATE INDEX CONCURRENTLY users_name_idx ON users (name ASC);\n\
+             INSERT INTO schema_migrations (version, name) VALUES (3, 'index names');",
+            migrations.up_script(Dialect::Postgres, &[1, 2]).unwrap()
+        );
+        assert_eq!(
+            "DROP INDEX users_name_idx;\n\
+             DELETE FROM schema_migrations WHERE version = 3;",
+            migrations
+                .down_script(Dialect::Postgres, &[1, 2, 3], 2)
+                .unwrap()
+        );
+    }
+
+    table! {
+        accounts {
+            id: i64,
+            name: String,
+            karma: i64,
+            email: Option<String>,
+        }
+    }
+
+    #[test]
+    fn test_typed_select_query() {
+        let (query, params) = query_builder::select(accounts::table)
+            .field(accounts::id)
+            .field(accounts::name)
+            .filter(accounts::karma.between(10, 20))
+            .filter(accounts::name.is_in(vec!["Jacob", "Alice"]))
+            .filter(accounts::email.is_not_null())
+            .order_by(accounts::karma, Order::Desc)
+            .build();
+        assert_eq!(
+            "SELECT accounts.id, accounts.name FROM accounts \
+             WHERE accounts.karma BETWEEN $1 AND $2 AND accounts.name IN ($3, $4) \
+             AND accounts.email IS NOT NULL ORDER BY accounts.karma DESC;",
+            query
+        );
+        assert_eq!(
+            vec![
+                Value::Int(10),
+                Value::Int(20),
+                Value::from("Jacob"),
+                Value::from("Alice"),
+            ],
+            params
+        );
+
+        let (query, _) = query_builder::select(accounts::table)
+            .join("posts", accounts::id, "posts.account_id", Join::Inner)
+            .build();
+        assert_eq!(
+            "SELECT * FROM accounts INNER JOIN posts ON accounts.id = posts.account_id;",
+            query
+        );
+
+        let (query, params) = query_builder::select(accounts::table)
+            .alias(accounts::table, "a")
+            .field(accounts::name.of("a"))
+            .join(
+                "posts",
+                accounts::id.of("a"),
+                "posts.account_id",
+                Join::Inner,
+            )
+            .filter(accounts::karma.of("a").gt(10))
             .build();
-        let possibility1 = "UPDATE users SET karma = 0 AND last_login = 1970-01-01;" == query;
-        let possibility2 = "UPDATE users SET last_login = 1970-01-01 AND karma = 0;" == query;
-        assert!(possibility1 || possibility2);
+        assert_eq!(
+            "SELECT a.name FROM accounts AS a INNER JOIN posts ON a.id = posts.account_id \
+             WHERE a.karma > $1;",
+            query
+        );
+        assert_eq!(vec![Value::Int(10)], params);
     }
 
     #[test]
-    fn test_update_query_with_conditions() {
-        let query = query_builder::update("users")
-            .set("karma", "0")
-            .filter("name = $1")
-            .filter("last_login < $2")
+    fn test_typed_update_query() {
+        let (query, params) = query_builder::update(accounts::table)
+            .set(accounts::name, "Jacob")
+            .set(accounts::email, None::<String>)
+            .set("karma", raw("karma + 1"))
+            .filter(accounts::id.eq(1))
+            .returning(accounts::karma)
+            .build();
+        assert_eq!(
+            "UPDATE accounts SET name = $1, email = $2, karma = karma + 1 WHERE accounts.id = $3 \
+             RETURNING accounts.karma;",
+            query
+        );
+        assert_eq!(
+            vec![Value::from("Jacob"), Value::Null, Value::Int(1)],
+            params
+        );
+
+        let (query, _) = query_builder::insert(accounts::table)
+            .set(accounts::id, 1)
+            .set(accounts::email, Some(String::from("jacob@example.com")))
+            .on_conflict(&["id"])
+            .do_update(accounts::karma, 0)
             .build();
         assert_eq!(
-            "UPDATE users SET karma = 0 WHERE name = $1 AND last_login < $2;",
+            "INSERT INTO accounts (id, email) VALUES ($1, $2) ON CONFLICT (id) DO UPDATE SET karma = $3;",
             query
         );
     }